@@ -0,0 +1,176 @@
+//! DuckDuckGo-style bangs, so searching `!gh tokio` goes straight to GitHub's
+//! search instead of ours.
+
+use std::collections::BTreeMap;
+
+use eyre::bail;
+use url::Url;
+
+use crate::config;
+
+/// Replaced with the url-encoded query in bang urls.
+pub const QUERY_PLACEHOLDER: &str = "{query}";
+
+const BUILTIN_BANGS: &[(&str, &str)] = &[
+    ("aw", "https://wiki.archlinux.org/index.php?search={query}"),
+    ("b", "https://www.bing.com/search?q={query}"),
+    ("brave", "https://search.brave.com/search?q={query}"),
+    ("crates", "https://crates.io/search?q={query}"),
+    ("ddg", "https://duckduckgo.com/?q={query}"),
+    ("g", "https://www.google.com/search?q={query}"),
+    (
+        "gh",
+        "https://github.com/search?q={query}&type=repositories",
+    ),
+    ("hn", "https://hn.algolia.com/?q={query}"),
+    (
+        "mdn",
+        "https://developer.mozilla.org/en-US/search?q={query}",
+    ),
+    ("npm", "https://www.npmjs.com/search?q={query}"),
+    ("pypi", "https://pypi.org/search/?q={query}"),
+    ("r", "https://www.reddit.com/search/?q={query}"),
+    ("rs", "https://docs.rs/releases/search?query={query}"),
+    ("rust", "https://doc.rust-lang.org/std/?search={query}"),
+    ("so", "https://stackoverflow.com/search?q={query}"),
+    ("w", "https://en.wikipedia.org/w/index.php?search={query}"),
+    ("wt", "https://en.wiktionary.org/w/index.php?search={query}"),
+    ("yt", "https://www.youtube.com/results?search_query={query}"),
+];
+
+/// Find the url for a bang, the user's own bangs take priority over the ones
+/// from the config, which take priority over the built-in ones.
+#[must_use]
+pub fn lookup<'a>(trigger: &str, user_bangs: &'a BTreeMap<String, String>) -> Option<&'a str> {
+    let trigger = trigger.to_lowercase();
+    user_bangs
+        .get(&trigger)
+        .or_else(|| config::get().bangs.get(&trigger))
+        .map(String::as_str)
+        .or_else(|| {
+            BUILTIN_BANGS
+                .iter()
+                .find(|(builtin_trigger, _)| *builtin_trigger == trigger)
+                .map(|(_, url)| *url)
+        })
+}
+
+/// If the query has a known bang in it, get the url it should redirect to.
+/// The bang can be anywhere in the query, like `!gh tokio` or `tokio !gh`.
+#[must_use]
+pub fn redirect_url(query: &str, user_bangs: &BTreeMap<String, String>) -> Option<String> {
+    let words = query.split_whitespace().collect::<Vec<_>>();
+    let (bang_index, url) = words.iter().enumerate().find_map(|(i, word)| {
+        let trigger = word.strip_prefix('!')?;
+        Some((i, lookup(trigger, user_bangs)?))
+    })?;
+
+    let rest = words
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != bang_index)
+        .map(|(_, word)| *word)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    // a bang on its own goes to the site itself
+    if rest.is_empty() {
+        if let Ok(url) = Url::parse(&url.replace(QUERY_PLACEHOLDER, "")) {
+            return Some(format!("{}/", url.origin().ascii_serialization()));
+        }
+    }
+
+    Some(url.replace(QUERY_PLACEHOLDER, &urlencoding::encode(&rest)))
+}
+
+/// All the bang triggers that start with the prefix, sorted so exact matches
+/// come first.
+#[must_use]
+pub fn suggest(prefix: &str, user_bangs: &BTreeMap<String, String>) -> Vec<String> {
+    let prefix = prefix.to_lowercase();
+    let mut triggers = user_bangs
+        .keys()
+        .chain(config::get().bangs.keys())
+        .map(String::as_str)
+        .chain(BUILTIN_BANGS.iter().map(|(trigger, _)| *trigger))
+        .filter(|trigger| trigger.starts_with(&prefix))
+        .collect::<Vec<_>>();
+    triggers.sort_by_key(|trigger| (*trigger != prefix, trigger.len(), *trigger));
+    triggers.dedup();
+    triggers.into_iter().map(str::to_string).collect()
+}
+
+/// Make sure a bang is usable, for bangs from the config and the ones people
+/// add in their settings.
+pub fn validate(trigger: &str, url: &str) -> eyre::Result<()> {
+    if trigger.is_empty()
+        || trigger.starts_with('!')
+        || trigger.contains(|c: char| c.is_whitespace() || c == ',' || c == '=')
+    {
+        bail!("invalid bang '{trigger}' (it can't be empty or contain spaces, commas or =)");
+    }
+    let Ok(parsed_url) = Url::parse(&url.replace(QUERY_PLACEHOLDER, "")) else {
+        bail!("invalid url for bang '{trigger}': {url}");
+    };
+    if !matches!(parsed_url.scheme(), "http" | "https") {
+        bail!("url for bang '{trigger}' must be http or https: {url}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bang_anywhere_in_query() {
+        let user_bangs = BTreeMap::new();
+        assert_eq!(
+            redirect_url("!gh tokio runtime", &user_bangs).as_deref(),
+            Some("https://github.com/search?q=tokio%20runtime&type=repositories")
+        );
+        assert_eq!(
+            redirect_url("tokio !GH", &user_bangs).as_deref(),
+            Some("https://github.com/search?q=tokio&type=repositories")
+        );
+    }
+
+    #[test]
+    fn bang_alone_goes_to_site() {
+        let user_bangs = BTreeMap::new();
+        assert_eq!(
+            redirect_url("!gh", &user_bangs).as_deref(),
+            Some("https://github.com/")
+        );
+    }
+
+    #[test]
+    fn unknown_bangs_are_searched() {
+        let user_bangs = BTreeMap::new();
+        assert_eq!(redirect_url("!notabang tokio", &user_bangs), None);
+        assert_eq!(redirect_url("hello world!", &user_bangs), None);
+        assert_eq!(redirect_url("tokio", &user_bangs), None);
+    }
+
+    #[test]
+    fn user_bangs_take_priority() {
+        let user_bangs = BTreeMap::from([
+            (
+                "g".to_string(),
+                "https://example.com/?q={query}".to_string(),
+            ),
+            (
+                "mine".to_string(),
+                "https://example.org/s/{query}".to_string(),
+            ),
+        ]);
+        assert_eq!(
+            redirect_url("!g a&b", &user_bangs).as_deref(),
+            Some("https://example.com/?q=a%26b")
+        );
+        assert_eq!(
+            redirect_url("rust !mine", &user_bangs).as_deref(),
+            Some("https://example.org/s/rust")
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! The command-line interface.
+
+use std::{collections::BTreeMap, path::PathBuf};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+
+use crate::{
+    config,
+    engines::{
+        self, Engine, Infobox, ProgressUpdateData, Response, SafeSearch, SearchQuery, TimeRange,
+    },
+    locale::Locale,
+    parse,
+};
+
+#[derive(Parser, Debug)]
+#[command(version, about = "a cute metasearch engine")]
+pub struct Cli {
+    /// Path to a TOML config file. Can also be set with the METASEARCH_CONFIG
+    /// environment variable.
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run the web server. This is the default if no command is given.
+    Serve {
+        /// The address to listen on, overrides the one in the config.
+        #[arg(long)]
+        bind: Option<String>,
+    },
+    /// Search for something and print the results to stdout.
+    Query {
+        /// The search query.
+        query: String,
+        /// Include scholarly engines in the search.
+        #[arg(long)]
+        scholarly: bool,
+        /// The page of results to get, from 1 to 100.
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=engines::MAX_PAGE as i64))]
+        page: u32,
+        /// The language and region to search in, like `en-US` or `de`.
+        #[arg(long, default_value = "en-US", value_parser = parse_locale)]
+        locale: Locale,
+        /// How much explicit content to filter out (off, moderate or strict),
+        /// defaults to the one in the config.
+        #[arg(long)]
+        safe_search: Option<SafeSearch>,
+        /// Only get results from the past day, week, month or year. Engines that
+        /// can't filter by time are skipped.
+        #[arg(long)]
+        time_range: Option<TimeRange>,
+        /// How the results should be printed.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// Rebuild the index for the local engine by crawling the sites and
+    /// sitemaps in the config's `local_index`.
+    #[cfg(feature = "local-index")]
+    Crawl,
+}
+
+fn parse_locale(locale: &str) -> Result<Locale, String> {
+    Locale::parse(locale).ok_or_else(|| format!("invalid locale '{locale}'"))
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Serialize)]
+struct QueryOutput {
+    #[serde(flatten)]
+    response: Response,
+    postsearch_infobox: Option<Infobox>,
+}
+
+pub async fn query(
+    query: String,
+    include_scholarly: bool,
+    page: usize,
+    locale: Locale,
+    safe_search: Option<SafeSearch>,
+    time_range: Option<TimeRange>,
+    format: OutputFormat,
+) -> eyre::Result<()> {
+    let enabled_engines = Engine::all()
+        .iter()
+        .map(|engine| (engine.id().to_string(), engine.is_enabled_by_default()))
+        .collect::<BTreeMap<_, _>>();
+
+    let query = SearchQuery {
+        query,
+        request_headers: Default::default(),
+        ip: "127.0.0.1".to_string(),
+        page,
+        locale,
+        safe_search: safe_search.unwrap_or(config::get().search.safe_search),
+        time_range,
+    };
+
+    let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
+    let search_future = tokio::spawn(async move {
+        engines::search(
+            query,
+            include_scholarly,
+            enabled_engines,
+            false,
+            progress_tx,
+        )
+        .await
+    });
+
+    let mut response = None;
+    let mut postsearch_infobox = None;
+    while let Some(progress_update) = progress_rx.recv().await {
+        match progress_update.data {
+            ProgressUpdateData::Engine { engine, update } => {
+                log::debug!("{}ms {engine} {update:?}", progress_update.time_ms);
+            }
+            ProgressUpdateData::PartialResponse(_) | ProgressUpdateData::EngineResponse { .. } => {}
+            ProgressUpdateData::Response(res) => response = Some(res),
+            ProgressUpdateData::PostSearchInfobox(infobox) => postsearch_infobox = Some(infobox),
+        }
+    }
+    search_future.await??;
+
+    let Some(response) = response else {
+        eyre::bail!("search finished without a response");
+    };
+
+    match format {
+        OutputFormat::Json => {
+            let output = QueryOutput {
+                response,
+                postsearch_infobox,
+            };
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputFormat::Text => print_text(&response, postsearch_infobox.as_ref()),
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "local-index")]
+pub async fn crawl() -> eyre::Result<()> {
+    let Some(local_index) = &config::get().local_index else {
+        eyre::bail!("there's no local_index in the config");
+    };
+    engines::local::crawl(local_index).await
+}
+
+fn print_text(response: &Response, postsearch_infobox: Option<&Infobox>) {
+    if let Some(infobox) = response.infobox.as_ref().or(postsearch_infobox) {
+        println!(
+            "[{}] {}\n",
+            infobox.engine,
+            parse::html_to_text(&infobox.html)
+        );
+    }
+    if let Some(answer) = &response.answer {
+        println!(
+            "[{}] {}\n",
+            answer.engine,
+            parse::html_to_text(&answer.html)
+        );
+    }
+    if let Some(featured_snippet) = &response.featured_snippet {
+        println!(
+            "{}\n{}\n{}\n[{}]\n",
+            featured_snippet.description,
+            featured_snippet.title,
+            featured_snippet.url,
+            featured_snippet.engine
+        );
+    }
+
+    if response.search_results.is_empty() && response.answer.is_none() {
+        println!("No results.");
+    }
+
+    for (i, result) in response.search_results.iter().enumerate() {
+        let engines = result
+            .engines
+            .iter()
+            .map(Engine::id)
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!(
+            "{n}. {title}\n   {url}\n   {description}\n   [{engines}]\n",
+            n = i + 1,
+            title = result.title.trim(),
+            url = result.url,
+            description = parse::strip_highlights(result.description.trim()),
+        );
+    }
+}
@@ -0,0 +1,311 @@
+//! Runtime configuration, loaded from a TOML file at startup.
+//!
+//! Everything in here is optional, anything that isn't set falls back to the
+//! defaults that are compiled into the binary.
+
+use std::{
+    collections::BTreeMap,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use eyre::{bail, eyre, WrapErr};
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+
+#[cfg(feature = "local-index")]
+use crate::engines::local::{LocalEngine, LocalIndexConfig};
+use crate::{
+    bangs,
+    engines::{
+        self,
+        custom::{
+            html::{HtmlEngine, HtmlEngineConfig},
+            json::{JsonEngine, JsonEngineConfig},
+        },
+        private::{PrivateEngine, PrivateEngineConfig},
+        ranking::RankingConfig,
+        Engine, SafeSearch,
+    },
+    web::BIND_ADDRESS,
+};
+
+/// The environment variable that can be used instead of `--config`.
+pub const CONFIG_ENV_VAR: &str = "METASEARCH_CONFIG";
+
+static CONFIG: OnceCell<Config> = OnceCell::new();
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The address the web server listens on.
+    pub bind: String,
+    pub search: SearchConfig,
+    pub cache: CacheConfig,
+    /// How results from different engines are combined.
+    pub ranking: RankingConfig,
+    /// Per-engine overrides, keyed by engine id.
+    pub engines: BTreeMap<String, EngineConfig>,
+    /// Extra engines that scrape html with css selectors, keyed by the id they
+    /// get registered with.
+    pub html_engines: BTreeMap<String, HtmlEngineConfig>,
+    /// Extra engines that get their results from a json api, keyed by the id
+    /// they get registered with.
+    pub json_engines: BTreeMap<String, JsonEngineConfig>,
+    /// Engines for Meilisearch, Typesense or Elasticsearch indexes that we run
+    /// ourselves, keyed by the id they get registered with.
+    pub private_engines: BTreeMap<String, PrivateEngineConfig>,
+    /// The index searched by the `local` engine, which is only registered if
+    /// this is set.
+    #[cfg(feature = "local-index")]
+    pub local_index: Option<LocalIndexConfig>,
+    /// A TOML file with extra bangs, mapping each bang to a url where
+    /// `{query}` gets replaced with the search. Relative paths are relative to
+    /// the config file.
+    pub bangs_file: Option<PathBuf>,
+    /// The bangs loaded from `bangs_file`.
+    #[serde(skip)]
+    pub bangs: BTreeMap<String, String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            bind: BIND_ADDRESS.to_string(),
+            search: SearchConfig::default(),
+            cache: CacheConfig::default(),
+            ranking: RankingConfig::default(),
+            engines: BTreeMap::new(),
+            html_engines: BTreeMap::new(),
+            json_engines: BTreeMap::new(),
+            private_engines: BTreeMap::new(),
+            #[cfg(feature = "local-index")]
+            local_index: None,
+            bangs_file: None,
+            bangs: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchConfig {
+    /// How long each engine gets to respond before it's considered timed out,
+    /// can be overridden per engine.
+    pub engine_timeout_ms: u64,
+    /// How long we wait for engines in total before merging the results we
+    /// have.
+    pub deadline_ms: u64,
+    /// The lowest weight an engine can have for its results to be rendered
+    /// before the other engines have responded.
+    pub first_render_min_weight: f64,
+    /// The weight of private engines, like a Meilisearch index of our own
+    /// docs, when it isn't set for the engine. This is higher than any public
+    /// engine by default so our own results come first.
+    pub private_engine_weight: f64,
+    /// The safe search level used when someone hasn't picked one in their
+    /// settings.
+    pub safe_search: SafeSearch,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            engine_timeout_ms: 5000,
+            deadline_ms: 8000,
+            first_render_min_weight: 1.,
+            private_engine_weight: 1.5,
+            safe_search: SafeSearch::default(),
+        }
+    }
+}
+
+impl SearchConfig {
+    #[must_use]
+    pub fn deadline(&self) -> Duration {
+        Duration::from_millis(self.deadline_ms)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    /// The maximum number of engine responses to keep, 0 disables the cache.
+    pub capacity: usize,
+    /// How long engine responses are kept for, 0 disables the cache.
+    pub ttl_secs: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1000,
+            ttl_secs: 300,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct EngineConfig {
+    pub weight: Option<f64>,
+    pub enabled: Option<bool>,
+    pub scholarly: Option<bool>,
+    pub timeout_ms: Option<u64>,
+}
+
+impl Config {
+    /// Parse a config, where relative paths in it are relative to `dir`.
+    // only the local index has paths that are resolved here
+    #[cfg_attr(not(feature = "local-index"), allow(unused_mut, unused_variables))]
+    pub fn from_toml(s: &str, dir: &Path) -> eyre::Result<Self> {
+        let mut config: Config = toml::from_str(s)?;
+        #[cfg(feature = "local-index")]
+        if let Some(local_index) = &mut config.local_index {
+            local_index.path = dir.join(&local_index.path);
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn read(path: &Path) -> eyre::Result<Self> {
+        let s = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("couldn't read config file {}", path.display()))?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let mut config = Self::from_toml(&s, dir)
+            .wrap_err_with(|| format!("invalid config file {}", path.display()))?;
+
+        if let Some(bangs_file) = &config.bangs_file {
+            let bangs_path = dir.join(bangs_file);
+            config.bangs = read_bangs(&bangs_path)
+                .wrap_err_with(|| format!("invalid bangs file {}", bangs_path.display()))?;
+        }
+
+        Ok(config)
+    }
+
+    /// Register the engines defined in the config and check that the engine
+    /// overrides are for engines that exist. This builds the engine registry,
+    /// so it should be called once at startup, after any other engines were
+    /// registered and before [`set`].
+    pub fn register_engines(&self) -> eyre::Result<()> {
+        for (id, html_engine_config) in &self.html_engines {
+            HtmlEngine::new(id, html_engine_config.clone())
+                .and_then(engines::register)
+                .wrap_err_with(|| format!("invalid html engine '{id}'"))?;
+        }
+        for (id, json_engine_config) in &self.json_engines {
+            JsonEngine::new(id, json_engine_config.clone())
+                .and_then(engines::register)
+                .wrap_err_with(|| format!("invalid json engine '{id}'"))?;
+        }
+        for (id, private_engine_config) in &self.private_engines {
+            PrivateEngine::new(id, private_engine_config.clone())
+                .and_then(engines::register)
+                .wrap_err_with(|| format!("invalid private engine '{id}'"))?;
+        }
+        #[cfg(feature = "local-index")]
+        if let Some(local_index) = &self.local_index {
+            local_index
+                .validate()
+                .and_then(|()| LocalEngine::open(local_index))
+                .and_then(engines::register)
+                .wrap_err("invalid local_index")?;
+        }
+
+        for id in self.engines.keys() {
+            if Engine::from_id(id).is_none() {
+                let known_ids = Engine::all()
+                    .iter()
+                    .map(|engine| engine.id())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                bail!("unknown engine '{id}' (expected one of: {known_ids})");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check the parts of the config that don't depend on which engines exist,
+    /// those are checked by [`Self::register_engines`].
+    pub fn validate(&self) -> eyre::Result<()> {
+        self.bind
+            .parse::<SocketAddr>()
+            .map_err(|err| eyre!("invalid bind address '{}': {err}", self.bind))?;
+
+        if self.search.engine_timeout_ms == 0 {
+            bail!("search.engine_timeout_ms must be greater than 0");
+        }
+        if self.search.deadline_ms == 0 {
+            bail!("search.deadline_ms must be greater than 0");
+        }
+        if !self.search.first_render_min_weight.is_finite() {
+            bail!("search.first_render_min_weight must be a number");
+        }
+        let private_engine_weight = self.search.private_engine_weight;
+        if !private_engine_weight.is_finite() || private_engine_weight < 0. {
+            bail!("invalid search.private_engine_weight: {private_engine_weight} (must be a non-negative number)");
+        }
+
+        if let RankingConfig::ReciprocalRankFusion { k } = self.ranking {
+            if !k.is_finite() || k < 0. {
+                bail!("invalid ranking.k: {k} (must be a non-negative number)");
+            }
+        }
+
+        for (id, engine_config) in &self.engines {
+            if let Some(weight) = engine_config.weight {
+                if !weight.is_finite() || weight < 0. {
+                    bail!("invalid weight for engine '{id}': {weight} (must be a non-negative number)");
+                }
+            }
+            if engine_config.timeout_ms == Some(0) {
+                bail!("invalid timeout_ms for engine '{id}': must be greater than 0");
+            }
+        }
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn engine(&self, engine: Engine) -> Option<&EngineConfig> {
+        self.engines.get(engine.id())
+    }
+}
+
+fn read_bangs(path: &Path) -> eyre::Result<BTreeMap<String, String>> {
+    let s = std::fs::read_to_string(path).wrap_err("couldn't read bangs file")?;
+    let bangs: BTreeMap<String, String> = toml::from_str(&s)?;
+    for (trigger, url) in &bangs {
+        bangs::validate(trigger, url)?;
+    }
+    Ok(bangs
+        .into_iter()
+        .map(|(trigger, url)| (trigger.to_lowercase(), url))
+        .collect())
+}
+
+/// Load the config from the given path, or from the path in
+/// [`CONFIG_ENV_VAR`] if there isn't one. If neither are set then the
+/// defaults are used.
+pub fn load(path: Option<&Path>) -> eyre::Result<Config> {
+    let env_path = std::env::var_os(CONFIG_ENV_VAR);
+    match path.or(env_path.as_deref().map(Path::new)) {
+        Some(path) => Config::read(path),
+        None => Ok(Config::default()),
+    }
+}
+
+/// Set the global config. This should only be called once at startup.
+pub fn set(config: Config) {
+    if CONFIG.set(config).is_err() {
+        log::warn!("Config was already set, ignoring");
+    }
+}
+
+/// Get the global config, or the default one if [`set`] was never called.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
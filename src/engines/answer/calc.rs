@@ -3,10 +3,38 @@ use std::cell::Cell;
 use fend_core::SpanKind;
 use once_cell::sync::Lazy;
 
-use crate::engines::EngineResponse;
+use crate::engines::{
+    operators::{ParsedQuery, QueryPart},
+    EngineKind, EngineResponse, RequestAutocompleteResponse, RequestResponse, SearchEngine,
+    SearchQuery,
+};
 
 use super::regex;
 
+pub struct Calc;
+
+impl SearchEngine for Calc {
+    fn id(&self) -> &str {
+        "calc"
+    }
+
+    fn name(&self) -> &str {
+        "Calculator"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::Answer
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        request(&query.query).into()
+    }
+
+    fn request_autocomplete(&self, query: &SearchQuery) -> Option<RequestAutocompleteResponse> {
+        Some(request_autocomplete(&query.query).into())
+    }
+}
+
 pub fn request(query: &str) -> EngineResponse {
     let query = clean_query(query);
 
@@ -156,9 +184,18 @@ fn evaluate_into_spans(query: &str, multiline: bool) -> Vec<Span> {
             return vec![];
         }
 
-        // probably a query operator thing or a url, fend evaluates these but it
-        // shouldn't
-        if regex!("^[a-z]{2,}:").is_match(query) {
+        // fend evaluates things like site:, inurl: and urls but it shouldn't.
+        // quotes and minus signs are fine since they mean something in fend too
+        let has_operators = ParsedQuery::parse(query).parts.iter().any(|part| {
+            matches!(
+                part,
+                QueryPart::Site(_)
+                    | QueryPart::ExcludeSite(_)
+                    | QueryPart::FileType(_)
+                    | QueryPart::InTitle(_)
+            )
+        });
+        if has_operators || regex!("^[a-z]{2,}:").is_match(query) {
             return vec![];
         }
 
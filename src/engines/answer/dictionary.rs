@@ -1,28 +1,67 @@
 use std::collections::HashMap;
 
 use eyre::eyre;
+use reqwest::StatusCode;
 use serde::Deserialize;
 use url::Url;
 
-use crate::engines::{EngineResponse, HttpResponse, RequestResponse, CLIENT};
+use crate::engines::{
+    EngineKind, EngineResponse, HttpResponse, RequestResponse, SearchEngine, SearchQuery, CLIENT,
+};
 
 use super::regex;
 
-pub fn request(query: &str) -> RequestResponse {
+pub struct Dictionary;
+
+impl SearchEngine for Dictionary {
+    fn id(&self) -> &str {
+        "dictionary"
+    }
+
+    fn name(&self) -> &str {
+        "Dictionary"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::Answer
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        request(query)
+    }
+
+    // wiktionary 404s for words it doesn't have
+    fn accepts_status(&self, status: StatusCode) -> bool {
+        status.is_success() || status == StatusCode::NOT_FOUND
+    }
+
+    fn parse_response(&self, res: &HttpResponse) -> eyre::Result<EngineResponse> {
+        parse_response(res)
+    }
+}
+
+pub fn request(query: &SearchQuery) -> RequestResponse {
+    // answers are only shown on the first page
+    if query.page > 1 {
+        return RequestResponse::None;
+    }
+
     // if the query starts with "define " then use that, otherwise abort
     let re = regex!(r"^define\s+(\w+)$");
-    let query = match re.captures(query) {
+    let word = match re.captures(&query.query) {
         Some(caps) => caps.get(1).unwrap().as_str(),
         None => return RequestResponse::None,
     }
     .to_lowercase();
 
+    // every language has its own wiktionary on a subdomain, like wikipedia
     CLIENT
         .get(
             Url::parse(
                 format!(
-                    "https://en.wiktionary.org/api/rest_v1/page/definition/{}",
-                    urlencoding::encode(&query)
+                    "https://{}.wiktionary.org/api/rest_v1/page/definition/{}",
+                    query.locale.language,
+                    urlencoding::encode(&word)
                 )
                 .as_str(),
             )
@@ -50,8 +89,11 @@ pub struct WiktionaryDefinition {
     pub examples: Vec<String>,
 }
 
-pub fn parse_response(HttpResponse { res, body }: &HttpResponse) -> eyre::Result<EngineResponse> {
+pub fn parse_response(
+    HttpResponse { res, body, locale }: &HttpResponse,
+) -> eyre::Result<EngineResponse> {
     let url = res.url();
+    let host = url.host_str().unwrap_or("en.wiktionary.org").to_string();
 
     let Ok(res) = serde_json::from_str::<WiktionaryResponse>(body) else {
         return Ok(EngineResponse::new());
@@ -60,19 +102,21 @@ pub fn parse_response(HttpResponse { res, body }: &HttpResponse) -> eyre::Result
     let mediawiki_key = url
         .path_segments()
         .ok_or_else(|| eyre!("url has no path segments"))?
-        .last()
+        .next_back()
         .ok_or_else(|| eyre!("url has no last path segment"))?;
 
     let word = key_to_title(mediawiki_key);
 
     let mut html = String::new();
 
-    let Some(entries) = res.0.get("en") else {
+    // wiktionary groups the entries by language, prefer the one the user is
+    // searching in but fall back to english
+    let Some(entries) = res.0.get(&locale.language).or_else(|| res.0.get("en")) else {
         return Ok(EngineResponse::new());
     };
 
     html.push_str(&format!(
-        "<h2 class=\"answer-dictionary-word\"><a href=\"https://en.wiktionary.org/wiki/{mediawiki_key}\">{word}</a></h2>",
+        "<h2 class=\"answer-dictionary-word\"><a href=\"https://{host}/wiki/{mediawiki_key}\">{word}</a></h2>",
         mediawiki_key = html_escape::encode_text(mediawiki_key),
         word = html_escape::encode_text(&word),
     ));
@@ -81,7 +125,7 @@ pub fn parse_response(HttpResponse { res, body }: &HttpResponse) -> eyre::Result
     cleaner
         .link_rel(None)
         .url_relative(ammonia::UrlRelative::RewriteWithBase(
-            Url::parse("https://en.wiktionary.org").unwrap(),
+            Url::parse(&format!("https://{host}")).unwrap(),
         ));
 
     for entry in entries {
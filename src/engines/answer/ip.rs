@@ -1,7 +1,27 @@
-use crate::engines::{EngineResponse, SearchQuery};
+use crate::engines::{EngineKind, EngineResponse, RequestResponse, SearchEngine, SearchQuery};
 
 use super::regex;
 
+pub struct Ip;
+
+impl SearchEngine for Ip {
+    fn id(&self) -> &str {
+        "ip"
+    }
+
+    fn name(&self) -> &str {
+        "IP"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::Answer
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        request(query).into()
+    }
+}
+
 pub fn request(query: &SearchQuery) -> EngineResponse {
     if !regex!("^what('s|s| is) my ip").is_match(&query.query.to_lowercase()) {
         return EngineResponse::new();
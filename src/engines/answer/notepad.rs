@@ -1,7 +1,27 @@
-use crate::engines::{EngineResponse, SearchQuery};
+use crate::engines::{EngineKind, EngineResponse, RequestResponse, SearchEngine, SearchQuery};
 
 use super::regex;
 
+pub struct Notepad;
+
+impl SearchEngine for Notepad {
+    fn id(&self) -> &str {
+        "notepad"
+    }
+
+    fn name(&self) -> &str {
+        "Notepad"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::Answer
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        request(query).into()
+    }
+}
+
 pub fn request(query: &SearchQuery) -> EngineResponse {
     if !regex!("(note|text|code) ?(pad|book|edit(or|er)?)").is_match(&query.query.to_lowercase()) {
         return EngineResponse::new();
@@ -3,10 +3,30 @@ use std::collections::HashMap;
 use axum::{extract::Query, http::header, response::IntoResponse};
 use rand::Rng;
 
-use crate::engines::{EngineResponse, SearchQuery};
+use crate::engines::{EngineKind, EngineResponse, RequestResponse, SearchEngine, SearchQuery};
 
 use super::regex;
 
+pub struct Random;
+
+impl SearchEngine for Random {
+    fn id(&self) -> &str {
+        "random"
+    }
+
+    fn name(&self) -> &str {
+        "Random"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::Answer
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        request(query).into()
+    }
+}
+
 pub fn request(query: &SearchQuery) -> EngineResponse {
     if !regex!("^(rng)|(rand(om)?( number( generator)?)?)").is_match(&query.query.to_lowercase()) {
         return EngineResponse::new();
@@ -1,10 +1,30 @@
 use chrono::{DateTime, TimeZone};
 use chrono_tz::{OffsetComponents, Tz};
 
-use crate::engines::EngineResponse;
+use crate::engines::{EngineKind, EngineResponse, RequestResponse, SearchEngine, SearchQuery};
 
 use super::regex;
 
+pub struct Timezone;
+
+impl SearchEngine for Timezone {
+    fn id(&self) -> &str {
+        "timezone"
+    }
+
+    fn name(&self) -> &str {
+        "Timezone"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::Answer
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        request(&query.query).into()
+    }
+}
+
 pub fn request(query: &str) -> EngineResponse {
     match evaluate(query) {
         None => EngineResponse::new(),
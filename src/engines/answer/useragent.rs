@@ -1,7 +1,27 @@
-use crate::engines::{EngineResponse, SearchQuery};
+use crate::engines::{EngineKind, EngineResponse, RequestResponse, SearchEngine, SearchQuery};
 
 use super::regex;
 
+pub struct Useragent;
+
+impl SearchEngine for Useragent {
+    fn id(&self) -> &str {
+        "useragent"
+    }
+
+    fn name(&self) -> &str {
+        "User Agent"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::Answer
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        request(query).into()
+    }
+}
+
 pub fn request(query: &SearchQuery) -> EngineResponse {
     if !regex!("^(what('s|s| is) my (user ?agent|ua)|ua|user ?agent)$")
         .is_match(&query.query.to_lowercase())
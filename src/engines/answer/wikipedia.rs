@@ -3,25 +3,60 @@ use std::collections::HashMap;
 use serde::Deserialize;
 use url::Url;
 
-use crate::engines::{EngineResponse, CLIENT};
-
-pub fn request(query: &str) -> reqwest::RequestBuilder {
-    CLIENT.get(
-        Url::parse_with_params(
-            "https://en.wikipedia.org/w/api.php",
-            &[
-                ("format", "json"),
-                ("action", "query"),
-                ("prop", "extracts|pageimages"),
-                ("exintro", ""),
-                ("explaintext", ""),
-                ("redirects", "1"),
-                ("exsentences", "2"),
-                ("titles", query),
-            ],
+use crate::engines::{
+    EngineKind, EngineResponse, HttpResponse, RequestResponse, SearchEngine, SearchQuery, CLIENT,
+};
+
+pub struct Wikipedia;
+
+impl SearchEngine for Wikipedia {
+    fn id(&self) -> &str {
+        "wikipedia"
+    }
+
+    fn name(&self) -> &str {
+        "Wikipedia"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::Answer
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        request(query)
+    }
+
+    fn parse_response(&self, res: &HttpResponse) -> eyre::Result<EngineResponse> {
+        parse_response(res)
+    }
+}
+
+pub fn request(query: &SearchQuery) -> RequestResponse {
+    // infoboxes are only shown on the first page, and queries with operators
+    // aren't going to be the title of an article
+    if query.page > 1 || query.parsed().has_operators() {
+        return RequestResponse::None;
+    }
+
+    // every language has its own wikipedia on a subdomain
+    CLIENT
+        .get(
+            Url::parse_with_params(
+                &format!("https://{}.wikipedia.org/w/api.php", query.locale.language),
+                &[
+                    ("format", "json"),
+                    ("action", "query"),
+                    ("prop", "extracts|pageimages"),
+                    ("exintro", ""),
+                    ("explaintext", ""),
+                    ("redirects", "1"),
+                    ("exsentences", "2"),
+                    ("titles", query.query.as_str()),
+                ],
+            )
+            .unwrap(),
         )
-        .unwrap(),
-    )
+        .into()
 }
 
 #[derive(Debug, Deserialize)]
@@ -51,7 +86,15 @@ pub struct WikipediaThumbnail {
     pub height: u64,
 }
 
-pub fn parse_response(body: &str) -> eyre::Result<EngineResponse> {
+pub fn parse_response(
+    HttpResponse { res, body, .. }: &HttpResponse,
+) -> eyre::Result<EngineResponse> {
+    let host = res
+        .url()
+        .host_str()
+        .unwrap_or("en.wikipedia.org")
+        .to_string();
+
     let Ok(res) = serde_json::from_str::<WikipediaResponse>(body) else {
         return Ok(EngineResponse::new());
     };
@@ -86,7 +129,7 @@ pub fn parse_response(body: &str) -> eyre::Result<EngineResponse> {
     }
 
     let page_title = title.replace(' ', "_");
-    let page_url = format!("https://en.wikipedia.org/wiki/{page_title}");
+    let page_url = format!("https://{host}/wiki/{page_title}");
 
     Ok(EngineResponse::infobox_html(format!(
         r#"<a href="{page_url}"><h2>{title}</h2></a><p>{extract}</p>"#,
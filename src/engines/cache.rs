@@ -0,0 +1,89 @@
+//! An in-memory cache of engine responses, so repeated searches don't have to
+//! request every engine again.
+
+use std::{
+    num::NonZeroUsize,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use lru::LruCache;
+use once_cell::sync::Lazy;
+
+use crate::{config, locale::Locale};
+
+use super::{Engine, EngineResponse, SafeSearch, SearchQuery, TimeRange};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    query: String,
+    engine: Engine,
+    include_scholarly: bool,
+    page: usize,
+    locale: Locale,
+    safe_search: SafeSearch,
+    time_range: Option<TimeRange>,
+}
+
+impl CacheKey {
+    #[must_use]
+    pub fn new(query: &SearchQuery, engine: Engine, include_scholarly: bool) -> Self {
+        Self {
+            query: normalize_query(&query.query),
+            engine,
+            include_scholarly,
+            page: query.page,
+            locale: query.locale.clone(),
+            safe_search: query.safe_search,
+            time_range: query.time_range,
+        }
+    }
+}
+
+struct CacheEntry {
+    response: EngineResponse,
+    inserted_at: Instant,
+}
+
+static CACHE: Lazy<Option<Mutex<LruCache<CacheKey, CacheEntry>>>> = Lazy::new(|| {
+    let capacity = NonZeroUsize::new(config::get().cache.capacity)?;
+    Some(Mutex::new(LruCache::new(capacity)))
+});
+
+fn ttl() -> Duration {
+    Duration::from_secs(config::get().cache.ttl_secs)
+}
+
+/// Collapse whitespace so trivially different queries share a cache entry.
+/// Case is kept since some engines (and the calculator) care about it.
+fn normalize_query(query: &str) -> String {
+    query.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Get a response from the cache if it's there and hasn't expired yet.
+#[must_use]
+pub fn get(key: &CacheKey) -> Option<EngineResponse> {
+    let mut cache = CACHE.as_ref()?.lock().unwrap();
+    let entry = cache.get(key)?;
+    if entry.inserted_at.elapsed() > ttl() {
+        cache.pop(key);
+        return None;
+    }
+    Some(entry.response.clone())
+}
+
+pub fn insert(key: CacheKey, response: EngineResponse) {
+    let Some(cache) = CACHE.as_ref() else {
+        return;
+    };
+    if ttl().is_zero() {
+        return;
+    }
+    cache.lock().unwrap().put(
+        key,
+        CacheEntry {
+            response,
+            inserted_at: Instant::now(),
+        },
+    );
+}
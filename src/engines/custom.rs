@@ -0,0 +1,42 @@
+//! Engines that are defined in the config instead of in code, so instance
+//! admins can add their own without forking.
+
+use eyre::{bail, eyre};
+use reqwest::Url;
+
+pub mod html;
+pub mod json;
+
+fn default_query_param() -> String {
+    "q".to_string()
+}
+
+/// Parse the url a custom engine searches with. This is done when the engine
+/// is created so a typo in the config doesn't only show up when searching.
+pub(super) fn parse_engine_url(url: &str) -> eyre::Result<Url> {
+    let parsed = Url::parse(url).map_err(|err| eyre!("invalid url '{url}': {err}"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        bail!("invalid url '{url}': must be http or https");
+    }
+    Ok(parsed)
+}
+
+/// The engine's url with the query, and the page if the engine has a page
+/// parameter, added on.
+fn search_url(
+    url: &Url,
+    query_param: &str,
+    page_param: Option<&str>,
+    query_string: &str,
+    page: usize,
+) -> Url {
+    let mut url = url.clone();
+    {
+        let mut query_pairs = url.query_pairs_mut();
+        query_pairs.append_pair(query_param, query_string);
+        if let Some(page_param) = page_param {
+            query_pairs.append_pair(page_param, &page.to_string());
+        }
+    }
+    url
+}
@@ -0,0 +1,168 @@
+use eyre::{bail, eyre};
+use reqwest::Url;
+use scraper::Selector;
+use serde::Deserialize;
+
+use super::{default_query_param, parse_engine_url, search_url};
+use crate::{
+    engines::{EngineResponse, HttpResponse, RequestResponse, SearchEngine, SearchQuery, CLIENT},
+    parse::{parse_html_response_with_opts, ParseOpts},
+};
+
+/// An engine that scrapes an html results page with css selectors, the same
+/// way most of the built-in search engines do.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct HtmlEngineConfig {
+    /// The name shown in the settings, defaults to the id.
+    pub name: Option<String>,
+    /// The url of the results page, which can already have query parameters
+    /// that are always sent.
+    pub url: String,
+    /// The query parameter the search is sent in.
+    #[serde(default = "default_query_param")]
+    pub query_param: String,
+    /// The query parameter the page number (starting at 1) is sent in, if the
+    /// engine has more than one page of results.
+    pub page_param: Option<String>,
+
+    /// Matches each search result.
+    pub result: String,
+    /// These are relative to the result. `href` uses the element's `href`
+    /// attribute, or its text if it doesn't have one.
+    pub title: String,
+    pub href: String,
+    pub description: String,
+
+    /// Matches the featured snippet, if the engine has one. The title, href
+    /// and description selectors have to be set with it.
+    pub featured_snippet: Option<String>,
+    pub featured_snippet_title: Option<String>,
+    pub featured_snippet_href: Option<String>,
+    pub featured_snippet_description: Option<String>,
+}
+
+pub struct HtmlEngine {
+    id: String,
+    url: Url,
+    config: HtmlEngineConfig,
+}
+
+impl HtmlEngine {
+    pub fn new(id: &str, config: HtmlEngineConfig) -> eyre::Result<Self> {
+        let url = parse_engine_url(&config.url)?;
+
+        // the featured snippet can't be parsed without knowing where each part
+        // of it is
+        if config.featured_snippet.is_some() {
+            let missing = [
+                ("featured_snippet_title", &config.featured_snippet_title),
+                ("featured_snippet_href", &config.featured_snippet_href),
+                (
+                    "featured_snippet_description",
+                    &config.featured_snippet_description,
+                ),
+            ]
+            .into_iter()
+            .filter(|(_, selector)| selector.is_none())
+            .map(|(field, _)| field)
+            .collect::<Vec<_>>();
+            if !missing.is_empty() {
+                bail!(
+                    "featured_snippet is set but {} isn't",
+                    missing.join(" and ")
+                );
+            }
+        }
+
+        let selectors = [
+            ("result", Some(&config.result)),
+            ("title", Some(&config.title)),
+            ("href", Some(&config.href)),
+            ("description", Some(&config.description)),
+            ("featured_snippet", config.featured_snippet.as_ref()),
+            (
+                "featured_snippet_title",
+                config.featured_snippet_title.as_ref(),
+            ),
+            (
+                "featured_snippet_href",
+                config.featured_snippet_href.as_ref(),
+            ),
+            (
+                "featured_snippet_description",
+                config.featured_snippet_description.as_ref(),
+            ),
+        ];
+        for (field, selector) in selectors {
+            if let Some(selector) = selector {
+                Selector::parse(selector)
+                    .map_err(|_| eyre!("invalid {field} selector '{selector}'"))?;
+            }
+        }
+
+        Ok(Self {
+            id: id.to_string(),
+            url,
+            config,
+        })
+    }
+}
+
+impl SearchEngine for HtmlEngine {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        self.config.name.as_deref().unwrap_or(&self.id)
+    }
+
+    fn supports_time_range(&self) -> bool {
+        false
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        // operators are left out, so a query with only those leaves nothing
+        // to search for
+        let query_string = query.query_for(self);
+        if query_string.is_empty() {
+            return RequestResponse::None;
+        }
+
+        CLIENT
+            .get(search_url(
+                &self.url,
+                &self.config.query_param,
+                self.config.page_param.as_deref(),
+                &query_string,
+                query.page,
+            ))
+            .into()
+    }
+
+    fn parse_response(&self, res: &HttpResponse) -> eyre::Result<EngineResponse> {
+        let config = &self.config;
+        let mut opts = ParseOpts::new()
+            .result(&config.result)
+            .title(config.title.as_str())
+            .href(config.href.as_str())
+            .description(config.description.as_str())
+            // links on results pages are often relative to the page
+            .base_url(&self.url);
+        if let (Some(featured_snippet), Some(title), Some(href), Some(description)) = (
+            &config.featured_snippet,
+            &config.featured_snippet_title,
+            &config.featured_snippet_href,
+            &config.featured_snippet_description,
+        ) {
+            opts = opts
+                .featured_snippet(featured_snippet)
+                .featured_snippet_title(title.as_str())
+                .featured_snippet_href(href.as_str())
+                .featured_snippet_description(description.as_str());
+        }
+
+        parse_html_response_with_opts(&res.body, opts)
+    }
+}
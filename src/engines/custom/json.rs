@@ -0,0 +1,290 @@
+use std::{collections::BTreeMap, fmt};
+
+use eyre::{bail, eyre};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Url,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{default_query_param, parse_engine_url, search_url};
+use crate::{
+    engines::{
+        EngineResponse, EngineSearchResult, HttpResponse, RequestResponse, SearchEngine,
+        SearchQuery, CLIENT,
+    },
+    normalize::normalize_url,
+};
+
+/// An engine that gets its results from a json api.
+///
+/// The paths to the values in the json are either JSON pointers like
+/// `/data/items`, or JSONPath like `$.data.items`. Only `.key`, `['key']` and
+/// `[index]` are supported in JSONPath, since every path has to lead to a
+/// single value.
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct JsonEngineConfig {
+    /// The name shown in the settings, defaults to the id.
+    pub name: Option<String>,
+    /// The url of the api, which can already have query parameters that are
+    /// always sent.
+    pub url: String,
+    /// The query parameter the search is sent in.
+    #[serde(default = "default_query_param")]
+    pub query_param: String,
+    /// The query parameter the page number (starting at 1) is sent in, if the
+    /// api has more than one page of results.
+    pub page_param: Option<String>,
+    /// Extra headers sent with every request, like an api key.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+
+    /// The path to the array of results.
+    pub results: String,
+    /// These are relative to each result. Results without a url are skipped.
+    pub href: String,
+    pub title: String,
+    pub description: Option<String>,
+}
+
+// not derived so header values like api keys don't end up in logs
+impl fmt::Debug for JsonEngineConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let headers = self
+            .headers
+            .keys()
+            .map(|name| (name, "<redacted>"))
+            .collect::<BTreeMap<_, _>>();
+        f.debug_struct("JsonEngineConfig")
+            .field("name", &self.name)
+            .field("url", &self.url)
+            .field("query_param", &self.query_param)
+            .field("page_param", &self.page_param)
+            .field("headers", &headers)
+            .field("results", &self.results)
+            .field("href", &self.href)
+            .field("title", &self.title)
+            .field("description", &self.description)
+            .finish()
+    }
+}
+
+pub struct JsonEngine {
+    id: String,
+    url: Url,
+    headers: HeaderMap,
+    results_pointer: String,
+    href_pointer: String,
+    title_pointer: String,
+    description_pointer: Option<String>,
+    config: JsonEngineConfig,
+}
+
+impl JsonEngine {
+    pub fn new(id: &str, config: JsonEngineConfig) -> eyre::Result<Self> {
+        let url = parse_engine_url(&config.url)?;
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &config.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| eyre!("invalid header name '{name}'"))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| eyre!("invalid value for header '{name}'"))?;
+            headers.insert(name, value);
+        }
+
+        Ok(Self {
+            id: id.to_string(),
+            url,
+            headers,
+            results_pointer: path_to_pointer(&config.results)?,
+            href_pointer: path_to_pointer(&config.href)?,
+            title_pointer: path_to_pointer(&config.title)?,
+            description_pointer: config
+                .description
+                .as_deref()
+                .map(path_to_pointer)
+                .transpose()?,
+            config,
+        })
+    }
+}
+
+impl SearchEngine for JsonEngine {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        self.config.name.as_deref().unwrap_or(&self.id)
+    }
+
+    fn supports_time_range(&self) -> bool {
+        false
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        // operators are left out, so a query with only those leaves nothing
+        // to search for
+        let query_string = query.query_for(self);
+        if query_string.is_empty() {
+            return RequestResponse::None;
+        }
+
+        CLIENT
+            .get(search_url(
+                &self.url,
+                &self.config.query_param,
+                self.config.page_param.as_deref(),
+                &query_string,
+                query.page,
+            ))
+            .headers(self.headers.clone())
+            .into()
+    }
+
+    fn parse_response(&self, res: &HttpResponse) -> eyre::Result<EngineResponse> {
+        let json: Value = serde_json::from_str(&res.body)?;
+
+        let results = match json.pointer(&self.results_pointer) {
+            Some(Value::Array(results)) => results.as_slice(),
+            // some apis leave the results out when there aren't any
+            Some(Value::Null) => &[],
+            Some(_) => bail!("'{}' in the response isn't an array", self.config.results),
+            None => bail!("no '{}' in the response", self.config.results),
+        };
+
+        let mut search_results = Vec::new();
+        for result in results {
+            let string_at = |pointer: &str| result.pointer(pointer).and_then(value_to_string);
+
+            let Some(url) = string_at(&self.href_pointer) else {
+                continue;
+            };
+            // apis for a single site often give paths instead of full urls
+            let url = self.url.join(&url).map_or(url, |url| url.to_string());
+            let Ok(url) = normalize_url(&url) else {
+                continue;
+            };
+            search_results.push(EngineSearchResult {
+                url,
+                title: string_at(&self.title_pointer).unwrap_or_default(),
+                description: self
+                    .description_pointer
+                    .as_deref()
+                    .and_then(string_at)
+                    .unwrap_or_default(),
+            });
+        }
+
+        Ok(EngineResponse {
+            search_results,
+            ..Default::default()
+        })
+    }
+}
+
+fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Turn a JSON pointer or one of the JSONPath paths we support into a JSON
+/// pointer, which is what serde_json understands.
+fn path_to_pointer(path: &str) -> eyre::Result<String> {
+    let Some(mut rest) = path.strip_prefix('$') else {
+        if !path.is_empty() && !path.starts_with('/') {
+            bail!("invalid path '{path}': must be a JSON pointer starting with / or JSONPath starting with $");
+        }
+        return Ok(path.to_string());
+    };
+
+    let mut pointer = String::new();
+    while !rest.is_empty() {
+        let key;
+        if let Some(after_dot) = rest.strip_prefix('.') {
+            let end = after_dot.find(['.', '[']).unwrap_or(after_dot.len());
+            (key, rest) = after_dot.split_at(end);
+        } else if let Some(after_bracket) = rest.strip_prefix('[') {
+            let Some(end) = after_bracket.find(']') else {
+                bail!("invalid path '{path}': unclosed [");
+            };
+            let inside = &after_bracket[..end];
+            rest = &after_bracket[end + 1..];
+            key = inside
+                .strip_prefix('\'')
+                .and_then(|inside| inside.strip_suffix('\''))
+                .or_else(|| {
+                    inside
+                        .strip_prefix('"')
+                        .and_then(|inside| inside.strip_suffix('"'))
+                })
+                .unwrap_or(inside);
+            if key == inside && !key.chars().all(|c| c.is_ascii_digit()) {
+                bail!("invalid path '{path}': only quoted keys and indexes are supported in []");
+            }
+        } else {
+            bail!("invalid path '{path}': expected . or [");
+        }
+
+        if key.is_empty() || key == "*" {
+            bail!("invalid path '{path}': only paths to a single value are supported");
+        }
+        pointer.push('/');
+        pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+    }
+    Ok(pointer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_pointer() {
+        assert_eq!(path_to_pointer("/a/b").unwrap(), "/a/b");
+        assert_eq!(path_to_pointer("").unwrap(), "");
+        assert!(path_to_pointer("a.b").is_err());
+    }
+
+    #[test]
+    fn dotted_path() {
+        assert_eq!(path_to_pointer("$.a.b").unwrap(), "/a/b");
+        assert_eq!(path_to_pointer("$").unwrap(), "");
+    }
+
+    #[test]
+    fn bracketed_path() {
+        assert_eq!(path_to_pointer("$['a'][0]").unwrap(), "/a/0");
+        assert_eq!(path_to_pointer(r#"$["a"].b[12]"#).unwrap(), "/a/b/12");
+        assert!(path_to_pointer("$[a]").is_err());
+        assert!(path_to_pointer("$['a'").is_err());
+    }
+
+    #[test]
+    fn escaped_keys() {
+        assert_eq!(path_to_pointer("$['a/b']['c~d']").unwrap(), "/a~1b/c~0d");
+        assert_eq!(path_to_pointer("$.~/").unwrap(), "/~0~1");
+    }
+
+    #[test]
+    fn wildcards_and_empty_keys() {
+        assert!(path_to_pointer("$[*]").is_err());
+        assert!(path_to_pointer("$.*").is_err());
+        assert!(path_to_pointer("$.a..b").is_err());
+        assert!(path_to_pointer("$[]").is_err());
+    }
+
+    #[test]
+    fn pointer_finds_value() {
+        let json: Value = serde_json::from_str(r#"{"a/b": [{"c~d": "found"}]}"#).unwrap();
+        let pointer = path_to_pointer("$['a/b'][0]['c~d']").unwrap();
+        assert_eq!(json.pointer(&pointer), Some(&Value::from("found")));
+    }
+}
@@ -0,0 +1,66 @@
+//! Deduplication of identical engine requests that are happening at the same
+//! time, so several people searching for the same thing at once only hit each
+//! upstream once.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use once_cell::sync::Lazy;
+use tokio::sync::watch;
+
+use super::{cache::CacheKey, EngineResponse};
+
+type InFlightResult = Option<Result<EngineResponse, String>>;
+
+static IN_FLIGHT: Lazy<Mutex<HashMap<CacheKey, watch::Receiver<InFlightResult>>>> =
+    Lazy::new(Default::default);
+
+pub enum InFlight {
+    /// Nobody else is making this request, so the caller should make it and
+    /// then call [`InFlightGuard::finish`].
+    Leader(InFlightGuard),
+    /// Someone else is already making this request, the result can be awaited
+    /// with [`wait`].
+    Follower(watch::Receiver<InFlightResult>),
+}
+
+/// Held by whoever is making the request. Other searches can wait on it until
+/// it's dropped.
+pub struct InFlightGuard {
+    key: CacheKey,
+    tx: watch::Sender<InFlightResult>,
+}
+
+impl InFlightGuard {
+    pub fn finish(self, result: &eyre::Result<EngineResponse>) {
+        let result = match result {
+            Ok(response) => Ok(response.clone()),
+            Err(err) => Err(err.to_string()),
+        };
+        self.tx.send_replace(Some(result));
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        IN_FLIGHT.lock().unwrap().remove(&self.key);
+    }
+}
+
+#[must_use]
+pub fn join(key: CacheKey) -> InFlight {
+    let mut in_flight = IN_FLIGHT.lock().unwrap();
+    if let Some(rx) = in_flight.get(&key) {
+        return InFlight::Follower(rx.clone());
+    }
+
+    let (tx, rx) = watch::channel(None);
+    in_flight.insert(key.clone(), rx);
+    InFlight::Leader(InFlightGuard { key, tx })
+}
+
+/// Wait for the leader to finish its request. Returns `None` if the leader was
+/// cancelled before it got a result.
+pub async fn wait(mut rx: watch::Receiver<InFlightResult>) -> InFlightResult {
+    let result = rx.wait_for(Option::is_some).await.ok()?;
+    result.clone()
+}
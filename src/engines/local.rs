@@ -0,0 +1,206 @@
+//! The local engine, which searches a tantivy index of pages from sites we
+//! picked. It works even when every upstream engine is ratelimiting us. The
+//! index is built with the `crawl` command.
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tantivy::{
+    collector::TopDocs,
+    directory::MmapDirectory,
+    query::QueryParser,
+    schema::{Field, Schema, Value, STORED, STRING, TEXT},
+    snippet::SnippetGenerator,
+    Index, IndexReader, ReloadPolicy, TantivyDocument,
+};
+
+use super::custom::parse_engine_url;
+use crate::engines::{
+    operators::OperatorSupport, EngineResponse, EngineSearchResult, RequestResponse, SearchEngine,
+    SearchQuery,
+};
+
+mod crawl;
+
+pub use crawl::crawl;
+
+const RESULTS_PER_PAGE: usize = 10;
+/// The most characters of a page shown in the description.
+const DESCRIPTION_LENGTH: usize = 200;
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct LocalIndexConfig {
+    /// Where the index is kept. Relative paths are relative to the config
+    /// file.
+    #[serde(default = "default_path")]
+    pub path: PathBuf,
+    /// Pages the crawler starts from. Links on them are followed to other
+    /// pages in the same directory or below it.
+    #[serde(default)]
+    pub sites: Vec<String>,
+    /// Sitemaps (or sitemap indexes) whose pages are all indexed, without
+    /// following their links.
+    #[serde(default)]
+    pub sitemaps: Vec<String>,
+    /// The most pages the crawler indexes.
+    #[serde(default = "default_max_pages")]
+    pub max_pages: usize,
+}
+
+fn default_path() -> PathBuf {
+    PathBuf::from("local-index")
+}
+
+fn default_max_pages() -> usize {
+    1000
+}
+
+impl LocalIndexConfig {
+    pub fn validate(&self) -> eyre::Result<()> {
+        for url in self.sites.iter().chain(&self.sitemaps) {
+            parse_engine_url(url)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Fields {
+    url: Field,
+    title: Field,
+    body: Field,
+}
+
+/// Open the index at the path, or create an empty one if there isn't one yet.
+fn open_index(path: &Path) -> eyre::Result<(Index, Fields)> {
+    let mut schema_builder = Schema::builder();
+    let fields = Fields {
+        url: schema_builder.add_text_field("url", STRING | STORED),
+        title: schema_builder.add_text_field("title", TEXT | STORED),
+        body: schema_builder.add_text_field("body", TEXT | STORED),
+    };
+    let schema = schema_builder.build();
+
+    std::fs::create_dir_all(path)?;
+    let index = Index::open_or_create(MmapDirectory::open(path)?, schema)?;
+    Ok((index, fields))
+}
+
+/// Cloning is cheap, the index is shared between the clones.
+#[derive(Clone)]
+pub struct LocalEngine {
+    reader: IndexReader,
+    query_parser: QueryParser,
+    fields: Fields,
+}
+
+impl LocalEngine {
+    pub fn open(config: &LocalIndexConfig) -> eyre::Result<Self> {
+        let (index, fields) = open_index(&config.path)?;
+        // this picks up the new index when the crawler finishes, so the server
+        // doesn't have to be restarted
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()?;
+
+        let mut query_parser = QueryParser::for_index(&index, vec![fields.title, fields.body]);
+        query_parser.set_conjunction_by_default();
+        query_parser.set_field_boost(fields.title, 2.);
+
+        Ok(Self {
+            reader,
+            query_parser,
+            fields,
+        })
+    }
+
+    fn search(&self, query_string: &str, offset: usize) -> eyre::Result<EngineResponse> {
+        let searcher = self.reader.searcher();
+        // lenient so queries with tantivy's syntax in them (like a stray colon)
+        // still search for the words instead of failing
+        let (tantivy_query, _) = self.query_parser.parse_query_lenient(query_string);
+        let top_docs = searcher.search(
+            &tantivy_query,
+            &TopDocs::with_limit(RESULTS_PER_PAGE).and_offset(offset),
+        )?;
+
+        let mut snippet_generator =
+            SnippetGenerator::create(&searcher, &*tantivy_query, self.fields.body)?;
+        snippet_generator.set_max_num_chars(DESCRIPTION_LENGTH);
+
+        let mut search_results = Vec::new();
+        for (_score, doc_address) in top_docs {
+            let doc: TantivyDocument = searcher.doc(doc_address)?;
+            let field_text = |field| {
+                doc.get_first(field)
+                    .and_then(|value| value.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+
+            let snippet = snippet_generator.snippet_from_doc(&doc);
+            // the snippet is empty if only the title matched
+            let description = if snippet.fragment().is_empty() {
+                field_text(self.fields.body)
+                    .chars()
+                    .take(DESCRIPTION_LENGTH)
+                    .collect()
+            } else {
+                snippet.fragment().to_string()
+            };
+
+            search_results.push(EngineSearchResult {
+                url: field_text(self.fields.url),
+                title: field_text(self.fields.title),
+                description,
+            });
+        }
+
+        Ok(EngineResponse {
+            search_results,
+            ..Default::default()
+        })
+    }
+}
+
+impl SearchEngine for LocalEngine {
+    fn id(&self) -> &str {
+        "local"
+    }
+
+    fn name(&self) -> &str {
+        "Local"
+    }
+
+    fn is_private(&self) -> bool {
+        true
+    }
+
+    fn supports_time_range(&self) -> bool {
+        false
+    }
+
+    fn operators(&self) -> OperatorSupport {
+        OperatorSupport {
+            phrases: true,
+            exclusions: true,
+            ..OperatorSupport::NONE
+        }
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        // operators other than phrases and exclusions are left out, so a query
+        // with only those leaves nothing to search for
+        let query_string = query.query_for(self);
+        if query_string.is_empty() {
+            return RequestResponse::None;
+        }
+
+        // searching reads from disk, so it can't happen on the runtime's thread
+        let engine = self.clone();
+        let offset = query.offset(RESULTS_PER_PAGE);
+        RequestResponse::Blocking(Box::new(move || engine.search(&query_string, offset)))
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::{HashSet, VecDeque};
+
+use eyre::bail;
+use reqwest::{header, Url};
+use scraper::{ElementRef, Html, Selector};
+use tantivy::{doc, IndexWriter};
+
+use super::{open_index, LocalIndexConfig};
+use crate::engines::{answer::regex, CLIENT};
+
+/// How much memory tantivy can use for indexing before it writes to disk.
+const WRITER_MEMORY_BYTES: usize = 50_000_000;
+
+struct Page {
+    title: String,
+    body: String,
+    links: Vec<Url>,
+}
+
+/// Rebuild the local index from the sites and sitemaps in the config.
+pub async fn crawl(config: &LocalIndexConfig) -> eyre::Result<()> {
+    if config.sites.is_empty() && config.sitemaps.is_empty() {
+        bail!("there are no sites or sitemaps to crawl in local_index");
+    }
+
+    let (index, fields) = open_index(&config.path)?;
+    let mut writer: IndexWriter = index.writer(WRITER_MEMORY_BYTES)?;
+    // everything is indexed again so pages that were removed from the sites go
+    // away too. searches keep using the old index until we commit at the end
+    writer.delete_all_documents()?;
+
+    // each page has the url that links on it are followed under, if any
+    let mut queue = VecDeque::<(Url, Option<String>)>::new();
+    let mut seen = HashSet::new();
+    for site in &config.sites {
+        let url = Url::parse(site)?;
+        let scope = url.join("./")?.to_string();
+        if seen.insert(url.clone()) {
+            queue.push_back((url, Some(scope)));
+        }
+    }
+    for sitemap in &config.sitemaps {
+        match sitemap_urls(sitemap).await {
+            Ok(urls) => {
+                for url in urls {
+                    if seen.insert(url.clone()) {
+                        queue.push_back((url, None));
+                    }
+                }
+            }
+            Err(err) => log::error!("Couldn't read sitemap {sitemap}: {err}"),
+        }
+    }
+
+    let mut indexed = 0;
+    while let Some((url, scope)) = queue.pop_front() {
+        if indexed >= config.max_pages {
+            log::warn!(
+                "Stopped after {} pages, {} were left",
+                config.max_pages,
+                queue.len() + 1
+            );
+            break;
+        }
+
+        let (mut url, page) = match fetch_page(url.clone()).await {
+            Ok(Some(page)) => page,
+            Ok(None) => continue,
+            Err(err) => {
+                log::warn!("Couldn't crawl {url}: {err}");
+                continue;
+            }
+        };
+
+        if let Some(scope) = &scope {
+            for link in page.links {
+                if link.as_str().starts_with(scope.as_str()) && seen.insert(link.clone()) {
+                    queue.push_back((link, Some(scope.clone())));
+                }
+            }
+        }
+
+        // this isn't normalized like results from other engines, since that
+        // makes it https and internal sites often only have http
+        url.set_fragment(None);
+        writer.add_document(doc!(
+            fields.url => url.to_string(),
+            fields.title => page.title,
+            fields.body => page.body,
+        ))?;
+        indexed += 1;
+        log::info!("Indexed {url}");
+    }
+
+    // committing would replace the old index with an empty one, which is
+    // probably because the sites are down rather than empty
+    if indexed == 0 {
+        bail!("no pages could be indexed, the old index was kept");
+    }
+
+    writer.commit()?;
+    log::info!("Indexed {indexed} pages into {}", config.path.display());
+    Ok(())
+}
+
+/// Every page url in a sitemap, following sitemap indexes to the sitemaps in
+/// them.
+async fn sitemap_urls(sitemap: &str) -> eyre::Result<Vec<Url>> {
+    let loc_re = regex!(r"<loc>\s*([^<]+?)\s*</loc>");
+
+    let mut urls = Vec::new();
+    let mut sitemaps = vec![Url::parse(sitemap)?];
+    let mut seen_sitemaps = HashSet::new();
+    while let Some(sitemap) = sitemaps.pop() {
+        if !seen_sitemaps.insert(sitemap.clone()) {
+            continue;
+        }
+        let body = CLIENT
+            .get(sitemap)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        let is_index = body.contains("<sitemapindex");
+        for capture in loc_re.captures_iter(&body) {
+            let Ok(url) = Url::parse(&html_escape::decode_html_entities(&capture[1])) else {
+                continue;
+            };
+            if is_index {
+                sitemaps.push(url);
+            } else {
+                urls.push(url);
+            }
+        }
+    }
+    Ok(urls)
+}
+
+/// Download and parse a page, returning the url it ended up at after
+/// redirects. Pages that aren't html are skipped.
+async fn fetch_page(url: Url) -> eyre::Result<Option<(Url, Page)>> {
+    let res = CLIENT.get(url).send().await?.error_for_status()?;
+    let is_html = res
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.contains("text/html"));
+    if !is_html {
+        return Ok(None);
+    }
+    let url = res.url().clone();
+    let body = res.text().await?;
+    let page = parse_page(&url, &body);
+    Ok(Some((url, page)))
+}
+
+fn parse_page(url: &Url, html: &str) -> Page {
+    let dom = Html::parse_document(html);
+
+    let title = dom
+        .select(&Selector::parse("title").unwrap())
+        .next()
+        .map(visible_text)
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| url.to_string());
+    let body = dom
+        .select(&Selector::parse("body").unwrap())
+        .next()
+        .map(visible_text)
+        .unwrap_or_default();
+
+    let links = dom
+        .select(&Selector::parse("a[href]").unwrap())
+        .filter_map(|a| url.join(a.value().attr("href")?).ok())
+        .filter(|link| matches!(link.scheme(), "http" | "https"))
+        .map(|mut link| {
+            link.set_fragment(None);
+            link
+        })
+        .collect();
+
+    Page { title, body, links }
+}
+
+/// The text in an element with whitespace collapsed, leaving out scripts and
+/// styles.
+fn visible_text(element: ElementRef) -> String {
+    let mut words = Vec::new();
+    for node in element.descendants() {
+        let Some(text) = node.value().as_text() else {
+            continue;
+        };
+        let is_hidden = node.ancestors().any(|ancestor| {
+            ancestor.value().as_element().is_some_and(|element| {
+                matches!(element.name(), "script" | "style" | "noscript" | "template")
+            })
+        });
+        if !is_hidden {
+            words.extend(text.split_whitespace());
+        }
+    }
+    words.join(" ")
+}
@@ -2,119 +2,86 @@ use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
     fmt,
     net::IpAddr,
-    ops::Deref,
     str::FromStr,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
-use futures::future::join_all;
+use futures::{future::join_all, stream::FuturesUnordered, StreamExt};
 use once_cell::sync::Lazy;
-use reqwest::header::HeaderMap;
+use reqwest::header::ACCEPT_LANGUAGE;
+use serde::{Deserialize, Serialize, Serializer};
 use tokio::sync::mpsc;
 
-mod macros;
-use crate::{
-    engine_autocomplete_requests, engine_enabled, engine_postsearch_requests, engine_requests,
-    engine_scholarly, engine_weights, engines,
-};
+mod cache;
+mod inflight;
+pub mod operators;
+pub mod ranking;
+mod registry;
+mod selectors;
+use crate::{config, locale::Locale};
+
+use operators::ParsedQuery;
+pub use registry::{register, Engine, EngineKind, SearchEngine};
+use selectors::EngineSelectors;
 
 pub mod answer;
+pub mod custom;
+#[cfg(feature = "local-index")]
+pub mod local;
 pub mod postsearch;
+pub mod private;
 pub mod search;
 
-engines! {
-    // search
-    Google = "google",
-    Bing = "bing",
-    Brave = "brave",
-    Marginalia = "marginalia",
-    GoogleScholar = "scholar",
-    Stract = "stract",
-    Rightdao = "rightdao",
-    Yep = "yep",
-    // answer
-    Useragent = "useragent",
-    Ip = "ip",
-    Calc = "calc",
-    Wikipedia = "wikipedia",
-    Dictionary = "dictionary",
-    Timezone = "timezone",
-    Notepad = "notepad",
-    Random = "random",
-    // post-search
-    StackExchange = "stackexchange",
-    GitHub = "github",
-    DocsRs = "docs.rs",
-    UrbanDictionary = "urban dictionary"
-}
-
-engine_weights! {
-    Google = 1.05,
-    GoogleScholar = 2.0,
-    Bing = 1.0,
-    Brave = 1.25,
-    Marginalia = 0.15,
-    Stract = 0.15,
-    Rightdao = 0.10,
-    Yep = 0.10,
-    // defaults to 1.0
-}
-
-engine_scholarly! {
-    Google = false,
-    GoogleScholar = true,
-    Bing = false,
-    Brave = false,
-    Marginalia = false,
-    Stract = false,
-    Rightdao = false,
-    Yep = false,
-    // defaults to false
-}
-
-engine_enabled! {
-    Google = true,
-    GoogleScholar = true,
-    Bing = true,
-    Brave = true,
-    Marginalia = true,
-    Stract = false,
-    Rightdao = false,
-    Yep = false,
-    // defaults to true
-}
-
-engine_requests! {
-    // search
-    Google => search::google::request, parse_response,
-    GoogleScholar => search::google_scholar::request, parse_response,
-    Bing => search::bing::request, parse_response,
-    Brave => search::brave::request, parse_response,
-    Marginalia => search::marginalia::request, parse_response,
-    Stract => search::stract::request, parse_response,
-    Rightdao => search::rightdao::request, parse_response,
-    Yep => search::yep::request, parse_response,
-    // answer
-    Useragent => answer::useragent::request, None,
-    Ip => answer::ip::request, None,
-    Calc => answer::calc::request, None,
-    Wikipedia => answer::wikipedia::request, parse_response,
-    Dictionary => answer::dictionary::request, parse_response,
-    Timezone => answer::timezone::request, None,
-    Notepad => answer::notepad::request, None,
-    Random => answer::random::request, None,
-}
-
-engine_autocomplete_requests! {
-    Google => search::google::request_autocomplete, parse_autocomplete_response,
-    Calc => answer::calc::request_autocomplete, None,
-}
-
-engine_postsearch_requests! {
-    StackExchange => postsearch::stackexchange::request, parse_response,
-    GitHub => postsearch::github::request, parse_response,
-    DocsRs => postsearch::docs_rs::request, parse_response,
-    UrbanDictionary => postsearch::urbandictionary::request, parse_response,
+impl Engine {
+    #[must_use]
+    pub fn weight(&self) -> f64 {
+        let config = config::get();
+        config
+            .engine(*self)
+            .and_then(|c| c.weight)
+            .unwrap_or_else(|| {
+                if self.is_private() {
+                    config.search.private_engine_weight
+                } else {
+                    self.default_weight()
+                }
+            })
+    }
+
+    #[must_use]
+    pub fn is_scholarly(&self) -> bool {
+        config::get()
+            .engine(*self)
+            .and_then(|c| c.scholarly)
+            .unwrap_or_else(|| self.default_scholarly())
+    }
+
+    #[must_use]
+    pub fn is_enabled_by_default(&self) -> bool {
+        config::get()
+            .engine(*self)
+            .and_then(|c| c.enabled)
+            .unwrap_or_else(|| self.default_enabled())
+    }
+
+    /// How long this engine gets to respond before it's considered timed out.
+    #[must_use]
+    pub fn timeout(&self) -> Duration {
+        let config = config::get();
+        Duration::from_millis(
+            config
+                .engine(*self)
+                .and_then(|c| c.timeout_ms)
+                .unwrap_or(config.search.engine_timeout_ms),
+        )
+    }
+
+    #[must_use]
+    pub fn is_enabled(&self, enabled_engines: &BTreeMap<String, bool>) -> bool {
+        *enabled_engines
+            .get(self.id())
+            .unwrap_or(&self.is_enabled_by_default())
+    }
 }
 
 impl fmt::Display for Engine {
@@ -123,17 +90,134 @@ impl fmt::Display for Engine {
     }
 }
 
+impl Serialize for Engine {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.id())
+    }
+}
+
+/// The last page of results that can be asked for. Engines rarely have useful
+/// results this far in, and it keeps the offsets we send them small.
+pub const MAX_PAGE: usize = 100;
+
 pub struct SearchQuery {
     pub query: String,
     pub request_headers: HashMap<String, String>,
     pub ip: String,
+    /// The page of results we want, from 1 to [`MAX_PAGE`].
+    pub page: usize,
+    /// The language and region the results should be in.
+    pub locale: Locale,
+    pub safe_search: SafeSearch,
+    /// Only get results from this far back, if set.
+    pub time_range: Option<TimeRange>,
+}
+
+impl SearchQuery {
+    #[must_use]
+    pub fn parsed(&self) -> ParsedQuery {
+        ParsedQuery::parse(&self.query)
+    }
+
+    /// The query in the syntax the engine understands, without the operators
+    /// it doesn't support.
+    #[must_use]
+    pub fn query_for(&self, engine: &dyn SearchEngine) -> String {
+        self.parsed().render(engine.operators())
+    }
+
+    /// The index of the first result on this page, for engines that paginate
+    /// with an offset instead of a page number.
+    #[must_use]
+    pub fn offset(&self, results_per_page: usize) -> usize {
+        (self.page.max(1) - 1).saturating_mul(results_per_page)
+    }
+}
+
+/// How much explicit content should be filtered out of the results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SafeSearch {
+    Off,
+    #[default]
+    Moderate,
+    /// Like moderate, but we also filter out results that look explicit
+    /// ourselves in case the engines missed them.
+    Strict,
+}
+
+impl SafeSearch {
+    pub const ALL: &'static [SafeSearch] = &[Self::Off, Self::Moderate, Self::Strict];
+
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Moderate => "moderate",
+            Self::Strict => "strict",
+        }
+    }
+}
+
+impl FromStr for SafeSearch {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|level| level.as_str() == s)
+            .copied()
+            .ok_or_else(|| {
+                format!("invalid safe search level '{s}' (expected off, moderate or strict)")
+            })
+    }
+}
+
+impl fmt::Display for SafeSearch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// How recent results have to be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeRange {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl TimeRange {
+    pub const ALL: &'static [TimeRange] = &[Self::Day, Self::Week, Self::Month, Self::Year];
+
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+            Self::Year => "year",
+        }
+    }
 }
 
-impl Deref for SearchQuery {
-    type Target = str;
+impl FromStr for TimeRange {
+    type Err = String;
 
-    fn deref(&self) -> &Self::Target {
-        &self.query
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|range| range.as_str() == s)
+            .copied()
+            .ok_or_else(|| format!("invalid time range '{s}' (expected day, week, month or year)"))
+    }
+}
+
+impl fmt::Display for TimeRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
     }
 }
 
@@ -141,6 +225,9 @@ pub enum RequestResponse {
     None,
     Http(reqwest::RequestBuilder),
     Instant(EngineResponse),
+    /// Work that blocks, like reading an index from disk. It's run on a
+    /// separate thread so other engines aren't held up.
+    Blocking(Box<dyn FnOnce() -> eyre::Result<EngineResponse> + Send>),
 }
 impl From<reqwest::RequestBuilder> for RequestResponse {
     fn from(req: reqwest::RequestBuilder) -> Self {
@@ -153,6 +240,7 @@ impl From<EngineResponse> for RequestResponse {
     }
 }
 
+#[allow(clippy::large_enum_variant)]
 pub enum RequestAutocompleteResponse {
     Http(reqwest::RequestBuilder),
     Instant(Vec<String>),
@@ -171,40 +259,61 @@ impl From<Vec<String>> for RequestAutocompleteResponse {
 pub struct HttpResponse {
     pub res: reqwest::Response,
     pub body: String,
+    /// The locale of the search that made the request.
+    pub locale: Locale,
 }
 
-impl<'a> From<&'a HttpResponse> for &'a str {
-    fn from(res: &'a HttpResponse) -> Self {
-        &res.body
-    }
-}
-
-impl From<HttpResponse> for reqwest::Response {
-    fn from(res: HttpResponse) -> Self {
-        res.res
-    }
-}
-
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct EngineSearchResult {
     pub url: String,
     pub title: String,
     pub description: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct EngineFeaturedSnippet {
     pub url: String,
     pub title: String,
     pub description: String,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize)]
 pub struct EngineResponse {
     pub search_results: Vec<EngineSearchResult>,
     pub featured_snippet: Option<EngineFeaturedSnippet>,
     pub answer_html: Option<String>,
     pub infobox_html: Option<String>,
+    /// Results the engine gave us that we left out while parsing, kept so
+    /// they can be shown in debug mode.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub filtered_results: Vec<(EngineSearchResult, FilterReason)>,
+}
+
+/// Why a result was left out of the results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterReason {
+    EmptyDescription,
+    Spam,
+    Explicit,
+    /// It didn't match an operator that the engine doesn't support.
+    Operators,
+    /// The user blocked the domain in their settings. This is only checked
+    /// when rendering, since it depends on the user's cookies.
+    BlockedDomain,
+}
+
+impl fmt::Display for FilterReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            Self::EmptyDescription => "empty description",
+            Self::Spam => "on the spam list",
+            Self::Explicit => "looks explicit",
+            Self::Operators => "didn't match the query's operators",
+            Self::BlockedDomain => "blocked domain",
+        };
+        write!(f, "{reason}")
+    }
 }
 
 impl EngineResponse {
@@ -213,6 +322,14 @@ impl EngineResponse {
         Self::default()
     }
 
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.search_results.is_empty()
+            && self.featured_snippet.is_none()
+            && self.answer_html.is_none()
+            && self.infobox_html.is_none()
+    }
+
     #[must_use]
     pub fn answer_html(html: String) -> Self {
         Self {
@@ -235,8 +352,16 @@ pub enum EngineProgressUpdate {
     Requesting,
     Downloading,
     Parsing,
-    Done,
+    /// The engine finished, `cached` is true if the response came from the
+    /// cache instead of being requested.
+    Done {
+        cached: bool,
+    },
     Skipping,
+    /// The engine failed, the string is the reason why.
+    Error(String),
+    /// The engine took longer than its timeout or the search deadline.
+    TimedOut,
 }
 
 #[derive(Debug)]
@@ -245,6 +370,17 @@ pub enum ProgressUpdateData {
         engine: Engine,
         update: EngineProgressUpdate,
     },
+    /// The merged results from the engines that have responded so far, sent
+    /// while we're still waiting for other engines. These are followed by more
+    /// partial responses and eventually a [`ProgressUpdateData::Response`].
+    PartialResponse(Response),
+    /// Sent as soon as an engine responds. The response from the engine before
+    /// it's merged with the others is only included in debug searches, since
+    /// it has to be cloned.
+    EngineResponse {
+        engine: Engine,
+        response: Option<EngineResponse>,
+    },
     Response(Response),
     PostSearchInfobox(Infobox),
 }
@@ -270,13 +406,18 @@ pub async fn search_with_engines(
     query: &SearchQuery,
     include_scholarly: bool,
     enabled_engines: BTreeMap<String, bool>,
+    debug: bool,
     progress_tx: mpsc::UnboundedSender<ProgressUpdate>,
 ) -> eyre::Result<()> {
     let start_time = Instant::now();
 
     let mut requests = Vec::new();
+    let mut pending_engines = BTreeSet::new();
     for engine in engines {
-        if (!include_scholarly && engine.is_scholarly()) || (!engine.is_enabled(&enabled_engines)) {
+        if (!include_scholarly && engine.is_scholarly())
+            || (!engine.is_enabled(&enabled_engines))
+            || (query.time_range.is_some() && !engine.supports_time_range())
+        {
             let engine = *engine;
             progress_tx.send(ProgressUpdate::new(
                 ProgressUpdateData::Engine {
@@ -290,80 +431,133 @@ pub async fn search_with_engines(
         requests.push(async {
             let engine = *engine;
 
-            let request_response = engine.request(query);
+            let cache_key = cache::CacheKey::new(query, engine, include_scholarly);
+            if let Some(response) = cache::get(&cache_key) {
+                progress_tx.send(ProgressUpdate::new(
+                    ProgressUpdateData::Engine {
+                        engine,
+                        update: EngineProgressUpdate::Done { cached: true },
+                    },
+                    start_time,
+                ))?;
+                return Ok((engine, Some(response)));
+            }
 
-            let response = match request_response {
-                RequestResponse::Http(request) => {
+            let response = match tokio::time::timeout(
+                engine.timeout(),
+                fetch_engine_response(engine, query, cache_key, &progress_tx, start_time),
+            )
+            .await
+            {
+                Ok(Ok(response)) => Some(response),
+                Ok(Err(err)) => {
+                    log::error!("Error from {engine} for '{}': {err}", query.query);
                     progress_tx.send(ProgressUpdate::new(
                         ProgressUpdateData::Engine {
                             engine,
-                            update: EngineProgressUpdate::Requesting,
+                            update: EngineProgressUpdate::Error(err.to_string()),
                         },
                         start_time,
                     ))?;
-
-                    let mut res = request.send().await?;
-
+                    None
+                }
+                Err(_) => {
+                    log::warn!("{engine} timed out for '{}'", query.query);
                     progress_tx.send(ProgressUpdate::new(
                         ProgressUpdateData::Engine {
                             engine,
-                            update: EngineProgressUpdate::Downloading,
+                            update: EngineProgressUpdate::TimedOut,
                         },
                         start_time,
                     ))?;
+                    None
+                }
+            };
 
-                    let mut body_bytes = Vec::new();
-                    while let Some(chunk) = res.chunk().await? {
-                        body_bytes.extend_from_slice(&chunk);
-                    }
-                    let body = String::from_utf8_lossy(&body_bytes).to_string();
-
-                    progress_tx.send(ProgressUpdate::new(
-                        ProgressUpdateData::Engine {
-                            engine,
-                            update: EngineProgressUpdate::Parsing,
-                        },
-                        start_time,
-                    ))?;
+            eyre::Ok((engine, response))
+        });
+        pending_engines.insert(*engine);
+    }
 
-                    let http_response = HttpResponse { res, body };
+    let mut response_futures = FuturesUnordered::new();
+    for request in requests {
+        response_futures.push(request);
+    }
 
-                    let response = match engine.parse_response(&http_response) {
-                        Ok(response) => response,
-                        Err(err) => {
-                            log::error!("Parse error for '{}': {err}", query.query);
-                            EngineResponse::new()
-                        }
+    // wait for the engines to finish, but stop at the deadline and merge the
+    // responses we have so far
+    let deadline = tokio::time::Instant::now() + config::get().search.deadline();
+    let first_render_min_weight = config::get().search.first_render_min_weight;
+    // a btreemap so the responses are always merged in the same order
+    let mut responses = BTreeMap::new();
+    let mut sent_partial_response = false;
+    loop {
+        match tokio::time::timeout_at(deadline, response_futures.next()).await {
+            Ok(Some(result)) => {
+                let (engine, response) = result?;
+                pending_engines.remove(&engine);
+                // engines that errored are left out, we merge whatever succeeded
+                let Some(mut response) = response else {
+                    continue;
+                };
+                progress_tx.send(ProgressUpdate::new(
+                    ProgressUpdateData::EngineResponse {
+                        engine,
+                        response: debug.then(|| response.clone()),
+                    },
+                    start_time,
+                ))?;
+                if query.page > 1 {
+                    // answers, infoboxes and featured snippets only go on the first page
+                    response = EngineResponse {
+                        search_results: response.search_results,
+                        filtered_results: response.filtered_results,
+                        ..Default::default()
                     };
+                }
 
+                // the first partial render waits for a search engine we trust,
+                // after that we re-render every time something new comes in
+                let should_render = if sent_partial_response {
+                    !response.is_empty()
+                } else {
+                    !response.search_results.is_empty()
+                        && engine.weight() >= first_render_min_weight
+                };
+                responses.insert(engine, response);
+
+                // if nothing is pending then the full response is sent right after this
+                if should_render && !response_futures.is_empty() {
+                    sent_partial_response = true;
+                    progress_tx.send(ProgressUpdate::new(
+                        ProgressUpdateData::PartialResponse(merge_engine_responses(
+                            query,
+                            responses.clone(),
+                            debug,
+                        )),
+                        start_time,
+                    ))?;
+                }
+            }
+            Ok(None) => break,
+            Err(_) => {
+                for engine in &pending_engines {
+                    log::warn!("{engine} missed the deadline for '{}'", query.query);
                     progress_tx.send(ProgressUpdate::new(
                         ProgressUpdateData::Engine {
-                            engine,
-                            update: EngineProgressUpdate::Done,
+                            engine: *engine,
+                            update: EngineProgressUpdate::TimedOut,
                         },
                         start_time,
                     ))?;
-
-                    response
                 }
-                RequestResponse::Instant(response) => response,
-                RequestResponse::None => EngineResponse::new(),
-            };
-
-            Ok((engine, response))
-        });
-    }
-
-    let mut response_futures = Vec::new();
-    for request in requests {
-        response_futures.push(request);
+                break;
+            }
+        }
     }
+    drop(response_futures);
 
-    let responses_result: eyre::Result<HashMap<_, _>> =
-        join_all(response_futures).await.into_iter().collect();
-    let responses = responses_result?;
-
-    let response = merge_engine_responses(responses);
+    let response = merge_engine_responses(query, responses, debug);
 
     let has_infobox = response.infobox.is_some();
 
@@ -372,28 +566,29 @@ pub async fn search_with_engines(
         start_time,
     ))?;
 
-    if !has_infobox {
+    if !has_infobox && query.page == 1 {
         // post-search
 
+        let public_response = response.without_private_results();
         let mut postsearch_requests = Vec::new();
         for engine in engines {
-            if let Some(request) = engine.postsearch_request(&response) {
+            if let Some(request) = engine.postsearch_request(&public_response) {
                 postsearch_requests.push(async {
-                    let response = match request.send().await {
-                        Ok(mut res) => {
-                            let mut body_bytes = Vec::new();
-                            while let Some(chunk) = res.chunk().await? {
-                                body_bytes.extend_from_slice(&chunk);
-                            }
-                            let body = String::from_utf8_lossy(&body_bytes).to_string();
-
-                            let http_response = HttpResponse { res, body };
-                            engine.postsearch_parse_response(&http_response)
-                        }
-                        Err(err) => {
+                    let response = match tokio::time::timeout(
+                        engine.timeout(),
+                        fetch_http_response(*engine, request, &query.locale),
+                    )
+                    .await
+                    {
+                        Ok(Ok(http_response)) => engine.postsearch_parse_response(&http_response),
+                        Ok(Err(err)) => {
                             log::error!("Postsearch request error for '{}': {err}", query.query);
                             None
                         }
+                        Err(_) => {
+                            log::warn!("Postsearch request for '{}' timed out", query.query);
+                            None
+                        }
                     };
                     Ok((*engine, response))
                 });
@@ -405,7 +600,7 @@ pub async fn search_with_engines(
             postsearch_response_futures.push(request);
         }
 
-        let postsearch_responses_result: eyre::Result<HashMap<_, _>> =
+        let postsearch_responses_result: eyre::Result<BTreeMap<_, _>> =
             join_all(postsearch_response_futures)
                 .await
                 .into_iter()
@@ -427,9 +622,148 @@ pub async fn search_with_engines(
     Ok(())
 }
 
+/// Request and parse the response for a single engine. Any errors returned
+/// from here only affect this engine, the rest of the search continues.
+async fn fetch_engine_response(
+    engine: Engine,
+    query: &SearchQuery,
+    cache_key: cache::CacheKey,
+    progress_tx: &mpsc::UnboundedSender<ProgressUpdate>,
+    start_time: Instant,
+) -> eyre::Result<EngineResponse> {
+    let request = match engine.request(query) {
+        RequestResponse::Http(request) => {
+            request.header(ACCEPT_LANGUAGE, query.locale.accept_language())
+        }
+        RequestResponse::Instant(response) => return Ok(response),
+        RequestResponse::Blocking(search) => return tokio::task::spawn_blocking(search).await?,
+        RequestResponse::None => return Ok(EngineResponse::new()),
+    };
+
+    progress_tx.send(ProgressUpdate::new(
+        ProgressUpdateData::Engine {
+            engine,
+            update: EngineProgressUpdate::Requesting,
+        },
+        start_time,
+    ))?;
+
+    // if another search is already making this exact request then wait for
+    // that instead of making it again
+    let guard = match inflight::join(cache_key.clone()) {
+        inflight::InFlight::Leader(guard) => Some(guard),
+        inflight::InFlight::Follower(rx) => match inflight::wait(rx).await {
+            Some(result) => {
+                let response = result.map_err(|err| eyre::eyre!(err))?;
+                progress_tx.send(ProgressUpdate::new(
+                    ProgressUpdateData::Engine {
+                        engine,
+                        update: EngineProgressUpdate::Done { cached: false },
+                    },
+                    start_time,
+                ))?;
+                return Ok(response);
+            }
+            // the other search was cancelled before it finished, so we have to
+            // make the request ourselves
+            None => None,
+        },
+    };
+
+    let result = request_and_parse(engine, request, &query.locale, progress_tx, start_time).await;
+    if let Some(guard) = guard {
+        guard.finish(&result);
+    }
+    let response = result?;
+
+    // only responses that had to be requested are cached, instant ones are
+    // cheap and can depend on things like the user's ip. the filtered results
+    // are kept too so a cached debug search still shows what was filtered out
+    cache::insert(cache_key, response.clone());
+
+    progress_tx.send(ProgressUpdate::new(
+        ProgressUpdateData::Engine {
+            engine,
+            update: EngineProgressUpdate::Done { cached: false },
+        },
+        start_time,
+    ))?;
+
+    Ok(response)
+}
+
+async fn request_and_parse(
+    engine: Engine,
+    request: reqwest::RequestBuilder,
+    locale: &Locale,
+    progress_tx: &mpsc::UnboundedSender<ProgressUpdate>,
+    start_time: Instant,
+) -> eyre::Result<EngineResponse> {
+    let res = request.send().await?;
+
+    progress_tx.send(ProgressUpdate::new(
+        ProgressUpdateData::Engine {
+            engine,
+            update: EngineProgressUpdate::Downloading,
+        },
+        start_time,
+    ))?;
+
+    let http_response = fetch_http_response_body(engine, res, locale).await?;
+
+    progress_tx.send(ProgressUpdate::new(
+        ProgressUpdateData::Engine {
+            engine,
+            update: EngineProgressUpdate::Parsing,
+        },
+        start_time,
+    ))?;
+
+    engine
+        .parse_response(&http_response)
+        .map_err(|err| eyre::eyre!("parse error: {err}"))
+}
+
+async fn fetch_http_response(
+    engine: Engine,
+    request: reqwest::RequestBuilder,
+    locale: &Locale,
+) -> eyre::Result<HttpResponse> {
+    let res = request
+        .header(ACCEPT_LANGUAGE, locale.accept_language())
+        .send()
+        .await?;
+    fetch_http_response_body(engine, res, locale).await
+}
+
+/// Download the body of the response, erroring if the engine doesn't accept
+/// the status code.
+async fn fetch_http_response_body(
+    engine: Engine,
+    mut res: reqwest::Response,
+    locale: &Locale,
+) -> eyre::Result<HttpResponse> {
+    let status = res.status();
+    if !engine.accepts_status(status) {
+        eyre::bail!("HTTP {status}");
+    }
+
+    let mut body_bytes = Vec::new();
+    while let Some(chunk) = res.chunk().await? {
+        body_bytes.extend_from_slice(&chunk);
+    }
+    let body = String::from_utf8_lossy(&body_bytes).to_string();
+
+    Ok(HttpResponse {
+        res,
+        body,
+        locale: locale.clone(),
+    })
+}
+
 pub async fn autocomplete_with_engines(
     engines: &[Engine],
-    query: &str,
+    query: &SearchQuery,
 ) -> eyre::Result<Vec<String>> {
     let mut requests = Vec::new();
     for engine in engines {
@@ -437,7 +771,10 @@ pub async fn autocomplete_with_engines(
             requests.push(async {
                 let response = match request {
                     RequestAutocompleteResponse::Http(request) => {
-                        let res = request.send().await?;
+                        let res = request
+                            .header(ACCEPT_LANGUAGE, query.locale.accept_language())
+                            .send()
+                            .await?;
                         let body = res.text().await?;
                         engine.parse_autocomplete_response(&body)?
                     }
@@ -453,7 +790,7 @@ pub async fn autocomplete_with_engines(
         autocomplete_futures.push(request);
     }
 
-    let autocomplete_results_result: eyre::Result<HashMap<_, _>> =
+    let autocomplete_results_result: eyre::Result<BTreeMap<_, _>> =
         join_all(autocomplete_futures).await.into_iter().collect();
     let autocomplete_results = autocomplete_results_result?;
 
@@ -466,55 +803,122 @@ pub static CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
         // we pretend to be a normal browser so websites don't block us
         // (since we're not entirely a bot, we're acting on behalf of the user)
         .user_agent("Mozilla/5.0 (Windows NT 10.0; rv:123.0) Gecko/20100101 Firefox/123.0")
-        .default_headers({
-            let mut headers = HeaderMap::new();
-            headers.insert("Accept-Language", "en-US,en;q=0.5".parse().unwrap());
-            headers
-        })
+        // the Accept-Language header is set per request from the search's locale
         .build()
         .unwrap()
 });
 
 pub async fn search(
-    query: SearchQuery,
-    include_scholarly: bool,
-    enabled_engines: BTreeMap<String, bool>,
+    mut query: SearchQuery,
+    mut include_scholarly: bool,
+    mut enabled_engines: BTreeMap<String, bool>,
+    debug: bool,
     progress_tx: mpsc::UnboundedSender<ProgressUpdate>,
 ) -> eyre::Result<()> {
-    let engines = Engine::all();
+    let mut engines = Engine::all().to_vec();
+
+    // engines picked in the query override the settings for this search. if
+    // the query is nothing but selectors then they're searched for as text
+    let (stripped_query, selectors) = EngineSelectors::extract(&query.query);
+    if !stripped_query.is_empty() {
+        query.query = stripped_query;
+        include_scholarly |= selectors.force_scholarly;
+        if !selectors.engines.is_empty() {
+            for engine in &selectors.engines {
+                enabled_engines.insert(engine.id().to_string(), true);
+                include_scholarly |= engine.is_scholarly();
+            }
+            // only the search engines are replaced, answer and post-search
+            // engines are still used unless they're disabled
+            engines.retain(|engine| {
+                engine.kind() != EngineKind::Search || selectors.engines.contains(engine)
+            });
+        }
+    }
+
     search_with_engines(
-        engines,
+        &engines,
         &query,
         include_scholarly,
         enabled_engines,
+        debug,
         progress_tx,
     )
     .await
 }
 
-pub async fn autocomplete(query: &str) -> eyre::Result<Vec<String>> {
+pub async fn autocomplete(query: &SearchQuery) -> eyre::Result<Vec<String>> {
     let engines = Engine::all();
     autocomplete_with_engines(engines, query).await
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Response {
     pub search_results: Vec<SearchResult>,
     pub featured_snippet: Option<FeaturedSnippet>,
     pub answer: Option<Answer>,
     pub infobox: Option<Infobox>,
+    /// Results that were left out, only kept for debug mode.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub filtered_results: Vec<FilteredResult>,
+}
+
+impl Response {
+    /// This response without the results and featured snippet that only came
+    /// from private engines.
+    #[must_use]
+    pub fn without_private_results(&self) -> Response {
+        let mut response = self.clone();
+        response
+            .search_results
+            .retain(|result| result.engines.iter().any(|engine| !engine.is_private()));
+        if response
+            .featured_snippet
+            .as_ref()
+            .is_some_and(|featured_snippet| featured_snippet.engine.is_private())
+        {
+            response.featured_snippet = None;
+        }
+        response
+            .filtered_results
+            .retain(|result| !result.engine.is_private());
+        response
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SearchResult {
     pub url: String,
     pub title: String,
     pub description: String,
     pub engines: BTreeSet<Engine>,
     pub score: f64,
+    /// Where each engine ranked this result and how much that added to the
+    /// score, in the order the engines responded.
+    pub rankings: Vec<EngineRanking>,
+    /// The engine whose title and description we're using.
+    pub title_engine: Engine,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+pub struct EngineRanking {
+    pub engine: Engine,
+    /// The position in the engine's results, starting at 1.
+    pub position: usize,
+    pub weight: f64,
+    /// How much this added to the result's score.
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FilteredResult {
+    pub url: String,
+    pub title: String,
+    pub engine: Engine,
+    pub reason: FilterReason,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct FeaturedSnippet {
     pub url: String,
     pub title: String,
@@ -522,30 +926,61 @@ pub struct FeaturedSnippet {
     pub engine: Engine,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Answer {
     pub html: String,
     pub engine: Engine,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Infobox {
     pub html: String,
     pub engine: Engine,
 }
 
-fn merge_engine_responses(responses: HashMap<Engine, EngineResponse>) -> Response {
+/// The filtered results are only kept if `debug` is set, since nothing else
+/// shows them.
+fn merge_engine_responses(
+    query: &SearchQuery,
+    responses: BTreeMap<Engine, EngineResponse>,
+    debug: bool,
+) -> Response {
+    let ranker = ranking::get();
+    // the engines already filter based on the safe search level, but in strict
+    // mode we also filter out anything that slipped through
+    let filter_explicit = query.safe_search == SafeSearch::Strict;
+    let parsed_query = query.parsed();
+
     let mut search_results: Vec<SearchResult> = Vec::new();
     let mut featured_snippet: Option<FeaturedSnippet> = None;
     let mut answer: Option<Answer> = None;
     let mut infobox: Option<Infobox> = None;
+    let mut filtered_results: Vec<FilteredResult> = Vec::new();
 
+    // the responses are in the same order as the engines are declared, and
+    // engines only replace the current featured snippet/answer/infobox if they
+    // have a strictly higher weight, so ties always go to the same engine
     for (engine, response) in responses {
+        if debug {
+            filtered_results.extend(response.filtered_results.into_iter().map(
+                |(result, reason)| FilteredResult {
+                    url: result.url,
+                    title: result.title,
+                    engine,
+                    reason,
+                },
+            ));
+        }
+
+        let result_count = response.search_results.len();
         for (result_index, search_result) in response.search_results.into_iter().enumerate() {
-            // position 1 has a score of 1, position 2 has a score of 0.5, position 3 has a
-            // score of 0.33, etc.
-            let base_result_score = 1. / (result_index + 1) as f64;
-            let result_score = base_result_score * engine.weight();
+            let result_score = ranker.score(engine, result_index, result_count);
+            let ranking = EngineRanking {
+                engine,
+                position: result_index + 1,
+                weight: engine.weight(),
+                score: result_score,
+            };
 
             if let Some(existing_result) = search_results
                 .iter_mut()
@@ -563,22 +998,39 @@ fn merge_engine_responses(responses: HashMap<Engine, EngineResponse>) -> Respons
                 {
                     existing_result.title = search_result.title;
                     existing_result.description = search_result.description;
+                    existing_result.title_engine = engine;
                 }
 
                 existing_result.engines.insert(engine);
                 existing_result.score += result_score;
-            } else if !filter_spam(&search_result.url) {
+                existing_result.rankings.push(ranking);
+            } else if let Some(reason) =
+                should_filter_result(engine, &search_result, &parsed_query, filter_explicit)
+            {
+                if debug {
+                    filtered_results.push(FilteredResult {
+                        url: search_result.url,
+                        title: search_result.title,
+                        engine,
+                        reason,
+                    });
+                }
+            } else {
                 search_results.push(SearchResult {
                     url: search_result.url,
                     title: search_result.title,
                     description: search_result.description,
                     engines: [engine].iter().copied().collect(),
                     score: result_score,
+                    rankings: vec![ranking],
+                    title_engine: engine,
                 });
             }
         }
 
-        if let Some(engine_featured_snippet) = response.featured_snippet {
+        if let Some(engine_featured_snippet) = response.featured_snippet.filter(|snippet| {
+            !(filter_explicit && is_explicit(&snippet.url, &snippet.title, &snippet.description))
+        }) {
             // if it has a higher weight than the current featured snippet
             let featured_snippet_weight =
                 featured_snippet.as_ref().map_or(0., |s| s.engine.weight());
@@ -622,6 +1074,7 @@ fn merge_engine_responses(responses: HashMap<Engine, EngineResponse>) -> Respons
         featured_snippet,
         answer,
         infobox,
+        filtered_results,
     }
 }
 
@@ -630,6 +1083,33 @@ pub struct AutocompleteResult {
     pub score: f64,
 }
 
+/// Why a result from this engine should be left out of the merged results, if
+/// it should be.
+fn should_filter_result(
+    engine: Engine,
+    result: &EngineSearchResult,
+    parsed_query: &ParsedQuery,
+    filter_explicit: bool,
+) -> Option<FilterReason> {
+    if filter_spam(&result.url) {
+        Some(FilterReason::Spam)
+    } else if filter_explicit && is_explicit(&result.url, &result.title, &result.description) {
+        Some(FilterReason::Explicit)
+    } else if !parsed_query.matches(result, engine.operators()) {
+        // operators the engine didn't understand are enforced here instead
+        Some(FilterReason::Operators)
+    } else {
+        None
+    }
+}
+
+/// Whether a result looks like it has explicit content.
+fn is_explicit(url: &str, title: &str, description: &str) -> bool {
+    [url, title, description]
+        .into_iter()
+        .any(rustrict::CensorStr::is_inappropriate)
+}
+
 fn filter_spam(url: &str) -> bool {
     const SPAM_LIST: &[&str] = &[
         "forgeeks",
@@ -663,15 +1143,14 @@ fn filter_spam(url: &str) -> bool {
     SPAM_LIST.iter().any(|e| url.contains(e))
 }
 
-fn merge_autocomplete_responses(responses: HashMap<Engine, Vec<String>>) -> Vec<String> {
+fn merge_autocomplete_responses(responses: BTreeMap<Engine, Vec<String>>) -> Vec<String> {
+    let ranker = ranking::get();
     let mut autocomplete_results: Vec<AutocompleteResult> = Vec::new();
 
     for (engine, response) in responses {
+        let result_count = response.len();
         for (result_index, autocomplete_result) in response.into_iter().enumerate() {
-            // position 1 has a score of 1, position 2 has a score of 0.5, position 3 has a
-            // score of 0.33, etc.
-            let base_result_score = 1. / (result_index + 1) as f64;
-            let result_score = base_result_score * engine.weight();
+            let result_score = ranker.score(engine, result_index, result_count);
 
             if let Some(existing_result) = autocomplete_results
                 .iter_mut()
@@ -0,0 +1,396 @@
+//! Parsing search operators like `site:`, `filetype:` and `"quoted phrases"`
+//! out of queries, so every engine can be given them in a syntax it
+//! understands and the ones it doesn't understand can be applied to its
+//! results afterwards.
+
+use std::fmt::Write;
+
+use url::Url;
+
+use super::EngineSearchResult;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryPart {
+    Term(String),
+    /// `"some words"`, the words should appear together.
+    Phrase(String),
+    /// `-word` or `-"some words"`.
+    Exclude(String),
+    /// `site:example.com`
+    Site(String),
+    /// `-site:example.com`
+    ExcludeSite(String),
+    /// `filetype:pdf` or `ext:pdf`
+    FileType(String),
+    /// `intitle:word`
+    InTitle(String),
+}
+
+/// A query split up into terms and operators.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedQuery {
+    pub parts: Vec<QueryPart>,
+}
+
+/// The operators an engine understands. Operators it doesn't understand are
+/// left out of the query we send it and are checked on its results instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OperatorSupport {
+    pub phrases: bool,
+    pub exclusions: bool,
+    pub site: bool,
+    pub exclude_site: bool,
+    pub filetype: bool,
+    pub intitle: bool,
+}
+
+impl OperatorSupport {
+    pub const ALL: Self = Self {
+        phrases: true,
+        exclusions: true,
+        site: true,
+        exclude_site: true,
+        filetype: true,
+        intitle: true,
+    };
+    pub const NONE: Self = Self {
+        phrases: false,
+        exclusions: false,
+        site: false,
+        exclude_site: false,
+        filetype: false,
+        intitle: false,
+    };
+}
+
+impl ParsedQuery {
+    #[must_use]
+    pub fn parse(query: &str) -> Self {
+        let mut parts = Vec::new();
+        let mut chars = query.chars().peekable();
+
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            if chars.peek().is_none() {
+                break;
+            }
+
+            let excluded = chars.next_if_eq(&'-').is_some();
+            if chars.next_if_eq(&'"').is_some() {
+                let phrase = take_quoted(&mut chars);
+                if phrase.is_empty() {
+                    continue;
+                }
+                parts.push(if excluded {
+                    QueryPart::Exclude(phrase)
+                } else {
+                    QueryPart::Phrase(phrase)
+                });
+                continue;
+            }
+
+            let mut word = String::new();
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                word.push(c);
+                // operator values can be quoted, like intitle:"some words"
+                if c == ':' && chars.next_if_eq(&'"').is_some() {
+                    word.push_str(&take_quoted(&mut chars));
+                    break;
+                }
+            }
+
+            parts.push(parse_word(&word, excluded));
+        }
+
+        Self { parts }
+    }
+
+    /// Whether the query has anything in it other than plain terms.
+    #[must_use]
+    pub fn has_operators(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|part| !matches!(part, QueryPart::Term(_)))
+    }
+
+    /// Turn the query back into a string for an engine, using the usual
+    /// `site:`/`-word`/`"phrase"` syntax for the operators it supports. Phrases
+    /// and `intitle:` are kept as plain terms if they're not supported, since
+    /// the words are still useful, everything else is left out.
+    #[must_use]
+    pub fn render(&self, support: OperatorSupport) -> String {
+        let mut rendered = String::new();
+        for part in &self.parts {
+            let start_len = rendered.len();
+            if !rendered.is_empty() {
+                rendered.push(' ');
+            }
+            match part {
+                QueryPart::Term(term) => rendered.push_str(term),
+                QueryPart::Phrase(phrase) if support.phrases => {
+                    write!(rendered, "\"{phrase}\"").unwrap();
+                }
+                QueryPart::Phrase(phrase) => rendered.push_str(phrase),
+                QueryPart::Exclude(term) if support.exclusions => {
+                    write!(rendered, "-{}", quote_if_needed(term)).unwrap();
+                }
+                QueryPart::Site(site) if support.site => {
+                    write!(rendered, "site:{site}").unwrap();
+                }
+                QueryPart::ExcludeSite(site) if support.exclude_site => {
+                    write!(rendered, "-site:{site}").unwrap();
+                }
+                QueryPart::FileType(filetype) if support.filetype => {
+                    write!(rendered, "filetype:{filetype}").unwrap();
+                }
+                QueryPart::InTitle(term) if support.intitle => {
+                    write!(rendered, "intitle:{}", quote_if_needed(term)).unwrap();
+                }
+                QueryPart::InTitle(term) => rendered.push_str(term),
+                QueryPart::Exclude(_)
+                | QueryPart::Site(_)
+                | QueryPart::ExcludeSite(_)
+                | QueryPart::FileType(_) => rendered.truncate(start_len),
+            }
+        }
+        rendered
+    }
+
+    /// Check a result against the operators the engine it came from doesn't
+    /// support. Phrases aren't checked since the description usually doesn't
+    /// have the whole page in it.
+    #[must_use]
+    pub fn matches(&self, result: &EngineSearchResult, support: OperatorSupport) -> bool {
+        let url = Url::parse(&result.url).ok();
+        let host = url
+            .as_ref()
+            .and_then(|url| url.host_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        let path = url
+            .as_ref()
+            .map(|url| url.path().to_lowercase())
+            .unwrap_or_default();
+        let title = result.title.to_lowercase();
+        let description = result.description.to_lowercase();
+
+        // multiple site: or filetype: operators mean any of them can match
+        let mut sites = Vec::new();
+        let mut filetypes = Vec::new();
+
+        for part in &self.parts {
+            match part {
+                QueryPart::Exclude(term) if !support.exclusions => {
+                    let term = term.to_lowercase();
+                    if title.contains(&term) || description.contains(&term) {
+                        return false;
+                    }
+                }
+                QueryPart::ExcludeSite(site)
+                    if !support.exclude_site && host_matches(&host, site) =>
+                {
+                    return false;
+                }
+                QueryPart::InTitle(term)
+                    if !support.intitle && !title.contains(&term.to_lowercase()) =>
+                {
+                    return false;
+                }
+                QueryPart::Site(site) if !support.site => sites.push(site),
+                QueryPart::FileType(filetype) if !support.filetype => filetypes.push(filetype),
+                _ => {}
+            }
+        }
+
+        if !sites.is_empty() && !sites.iter().any(|site| host_matches(&host, site)) {
+            return false;
+        }
+        if !filetypes.is_empty()
+            && !filetypes
+                .iter()
+                .any(|filetype| path.ends_with(&format!(".{filetype}")))
+        {
+            return false;
+        }
+
+        true
+    }
+}
+
+fn parse_word(word: &str, excluded: bool) -> QueryPart {
+    if let Some((operator, value)) = word.split_once(':') {
+        if !value.is_empty() {
+            let value = value.to_string();
+            match (operator.to_lowercase().as_str(), excluded) {
+                ("site", false) => return QueryPart::Site(value.to_lowercase()),
+                ("site", true) => return QueryPart::ExcludeSite(value.to_lowercase()),
+                ("filetype" | "ext", false) => {
+                    return QueryPart::FileType(value.trim_start_matches('.').to_lowercase())
+                }
+                ("intitle", false) => return QueryPart::InTitle(value),
+                _ => {}
+            }
+        }
+    }
+
+    // a minus followed by a number is probably a negative number and not an
+    // exclusion
+    let is_number = word.starts_with(|c: char| c.is_ascii_digit());
+    if excluded && !word.is_empty() && !is_number {
+        QueryPart::Exclude(word.to_string())
+    } else if excluded {
+        QueryPart::Term(format!("-{word}"))
+    } else {
+        QueryPart::Term(word.to_string())
+    }
+}
+
+/// Read until the closing quote, or the end of the query if there isn't one.
+fn take_quoted(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut quoted = String::new();
+    for c in chars.by_ref() {
+        if c == '"' {
+            break;
+        }
+        quoted.push(c);
+    }
+    quoted.trim().to_string()
+}
+
+fn quote_if_needed(term: &str) -> String {
+    if term.contains(char::is_whitespace) {
+        format!("\"{term}\"")
+    } else {
+        term.to_string()
+    }
+}
+
+/// Whether the host is the site or a subdomain of it. Paths in the site (like
+/// `site:github.com/rust-lang`) are ignored.
+fn host_matches(host: &str, site: &str) -> bool {
+    let site = site.split('/').next().unwrap_or_default();
+    let site = site.trim_start_matches("www.");
+    let host = host.trim_start_matches("www.");
+    host == site || host.ends_with(&format!(".{site}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(url: &str, title: &str, description: &str) -> EngineSearchResult {
+        EngineSearchResult {
+            url: url.to_string(),
+            title: title.to_string(),
+            description: description.to_string(),
+        }
+    }
+
+    #[test]
+    fn parse_operators() {
+        let parsed = ParsedQuery::parse(
+            r#"rust "borrow checker" -unsafe site:Docs.rs -site:reddit.com filetype:.PDF intitle:"the book""#,
+        );
+        assert_eq!(
+            parsed.parts,
+            vec![
+                QueryPart::Term("rust".to_string()),
+                QueryPart::Phrase("borrow checker".to_string()),
+                QueryPart::Exclude("unsafe".to_string()),
+                QueryPart::Site("docs.rs".to_string()),
+                QueryPart::ExcludeSite("reddit.com".to_string()),
+                QueryPart::FileType("pdf".to_string()),
+                QueryPart::InTitle("the book".to_string()),
+            ]
+        );
+        assert!(parsed.has_operators());
+    }
+
+    #[test]
+    fn parse_plain_words() {
+        let parsed = ParsedQuery::parse("  -3+4  inurl:x site: \"\" ");
+        assert_eq!(
+            parsed.parts,
+            vec![
+                QueryPart::Term("-3+4".to_string()),
+                QueryPart::Term("inurl:x".to_string()),
+                QueryPart::Term("site:".to_string()),
+            ]
+        );
+        assert!(!parsed.has_operators());
+    }
+
+    #[test]
+    fn parse_unclosed_quote() {
+        let parsed = ParsedQuery::parse(r#"-"some words"#);
+        assert_eq!(
+            parsed.parts,
+            vec![QueryPart::Exclude("some words".to_string())]
+        );
+    }
+
+    #[test]
+    fn render_with_all_operators() {
+        let query = r#"rust "borrow checker" -"raw pointers" site:docs.rs -site:reddit.com filetype:pdf intitle:"the book""#;
+        assert_eq!(
+            ParsedQuery::parse(query).render(OperatorSupport::ALL),
+            query
+        );
+    }
+
+    #[test]
+    fn render_without_operators() {
+        let parsed = ParsedQuery::parse(
+            r#"rust "borrow checker" -unsafe site:docs.rs filetype:pdf intitle:book"#,
+        );
+        assert_eq!(
+            parsed.render(OperatorSupport::NONE),
+            "rust borrow checker book"
+        );
+    }
+
+    #[test]
+    fn render_only_operators_is_empty() {
+        let parsed = ParsedQuery::parse("site:example.com -site:reddit.com filetype:pdf");
+        assert_eq!(parsed.render(OperatorSupport::NONE), "");
+    }
+
+    #[test]
+    fn matches_site() {
+        let parsed = ParsedQuery::parse("site:example.com site:docs.rs/tokio");
+        let support = OperatorSupport::NONE;
+        assert!(parsed.matches(&result("https://www.example.com/a", "", ""), support));
+        assert!(parsed.matches(&result("https://sub.example.com/a", "", ""), support));
+        assert!(parsed.matches(&result("https://docs.rs/serde", "", ""), support));
+        assert!(!parsed.matches(&result("https://notexample.com/a", "", ""), support));
+        // engines that support the operator are trusted to have applied it
+        assert!(parsed.matches(
+            &result("https://notexample.com/a", "", ""),
+            OperatorSupport::ALL
+        ));
+    }
+
+    #[test]
+    fn matches_exclusions() {
+        let parsed = ParsedQuery::parse("-Unsafe -site:reddit.com");
+        let support = OperatorSupport::NONE;
+        assert!(parsed.matches(&result("https://a.com", "safe", "code"), support));
+        assert!(!parsed.matches(&result("https://a.com", "UNSAFE code", ""), support));
+        assert!(!parsed.matches(&result("https://old.reddit.com/r/rust", "", ""), support));
+    }
+
+    #[test]
+    fn matches_filetype_and_intitle() {
+        let parsed = ParsedQuery::parse("filetype:pdf intitle:Guide");
+        let support = OperatorSupport::NONE;
+        assert!(parsed.matches(
+            &result("https://a.com/The-Guide.PDF", "The guide", ""),
+            support
+        ));
+        assert!(!parsed.matches(
+            &result("https://a.com/guide.html", "The guide", ""),
+            support
+        ));
+        assert!(!parsed.matches(&result("https://a.com/x.pdf", "Manual", ""), support));
+    }
+}
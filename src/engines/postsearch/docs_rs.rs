@@ -1,6 +1,30 @@
 use scraper::{Html, Selector};
 
-use crate::engines::{HttpResponse, Response, CLIENT};
+use crate::engines::{EngineKind, HttpResponse, Response, SearchEngine, CLIENT};
+
+pub struct DocsRs;
+
+impl SearchEngine for DocsRs {
+    fn id(&self) -> &str {
+        "docs.rs"
+    }
+
+    fn name(&self) -> &str {
+        "docs.rs"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::PostSearch
+    }
+
+    fn postsearch_request(&self, response: &Response) -> Option<reqwest::RequestBuilder> {
+        request(response)
+    }
+
+    fn postsearch_parse_response(&self, res: &HttpResponse) -> Option<String> {
+        parse_response(res)
+    }
+}
 
 pub fn request(response: &Response) -> Option<reqwest::RequestBuilder> {
     for search_result in response.search_results.iter().take(8) {
@@ -12,7 +36,7 @@ pub fn request(response: &Response) -> Option<reqwest::RequestBuilder> {
     None
 }
 
-pub fn parse_response(HttpResponse { res, body }: &HttpResponse) -> Option<String> {
+pub fn parse_response(HttpResponse { res, body, .. }: &HttpResponse) -> Option<String> {
     let url = res.url().clone();
 
     let dom = Html::parse_document(body);
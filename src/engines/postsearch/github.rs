@@ -1,11 +1,36 @@
 use scraper::{Html, Selector};
 use url::Url;
 
-use crate::engines::{answer::regex, Response, CLIENT};
+use crate::engines::{answer::regex, EngineKind, HttpResponse, Response, SearchEngine, CLIENT};
+
+pub struct GitHub;
+
+impl SearchEngine for GitHub {
+    fn id(&self) -> &str {
+        "github"
+    }
+
+    fn name(&self) -> &str {
+        "GitHub"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::PostSearch
+    }
+
+    fn postsearch_request(&self, response: &Response) -> Option<reqwest::RequestBuilder> {
+        request(response)
+    }
+
+    fn postsearch_parse_response(&self, res: &HttpResponse) -> Option<String> {
+        parse_response(&res.body)
+    }
+}
 
 pub fn request(response: &Response) -> Option<reqwest::RequestBuilder> {
+    let re = regex!(r"^https:\/\/github\.com\/[\w-]+\/[\w.-]+$");
     for search_result in response.search_results.iter().take(8) {
-        if regex!(r"^https:\/\/github\.com\/[\w-]+\/[\w.-]+$").is_match(&search_result.url) {
+        if re.is_match(&search_result.url) {
             return Some(CLIENT.get(search_result.url.as_str()));
         }
     }
@@ -28,7 +53,7 @@ pub fn parse_response(body: &str) -> Option<String> {
 
     let embedded_data_script = dom
         .select(&Selector::parse("script[data-target='react-partial.embeddedData']").unwrap())
-        .last()?
+        .next_back()?
         .inner_html();
     let embedded_data = serde_json::from_str::<serde_json::Value>(&embedded_data_script).ok()?;
     let readme_html = embedded_data
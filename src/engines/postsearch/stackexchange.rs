@@ -1,13 +1,38 @@
 use scraper::{Html, Selector};
 use url::Url;
 
-use crate::engines::{answer::regex, Response, CLIENT};
+use crate::engines::{answer::regex, EngineKind, HttpResponse, Response, SearchEngine, CLIENT};
+
+pub struct StackExchange;
+
+impl SearchEngine for StackExchange {
+    fn id(&self) -> &str {
+        "stackexchange"
+    }
+
+    fn name(&self) -> &str {
+        "Stack Exchange"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::PostSearch
+    }
+
+    fn postsearch_request(&self, response: &Response) -> Option<reqwest::RequestBuilder> {
+        request(response)
+    }
+
+    fn postsearch_parse_response(&self, res: &HttpResponse) -> Option<String> {
+        parse_response(&res.body)
+    }
+}
 
 pub fn request(response: &Response) -> Option<reqwest::RequestBuilder> {
+    let re = regex!(
+        r"^https:\/\/(stackoverflow\.com|serverfault\.com|superuser\.com|\w{1,}\.stackexchange\.com)\/questions\/\d+"
+    );
     for search_result in response.search_results.iter().take(8) {
-        if regex!(r"^https:\/\/(stackoverflow\.com|serverfault\.com|superuser\.com|\w{1,}\.stackexchange\.com)\/questions\/\d+")
-            .is_match(&search_result.url)
-        {
+        if re.is_match(&search_result.url) {
             return Some(CLIENT.get(search_result.url.as_str()));
         }
     }
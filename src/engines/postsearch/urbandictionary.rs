@@ -1,12 +1,35 @@
 use scraper::{Html, Selector};
 
-use crate::engines::{answer::regex, Response, CLIENT};
+use crate::engines::{answer::regex, EngineKind, HttpResponse, Response, SearchEngine, CLIENT};
+
+pub struct UrbanDictionary;
+
+impl SearchEngine for UrbanDictionary {
+    fn id(&self) -> &str {
+        "urban dictionary"
+    }
+
+    fn name(&self) -> &str {
+        "Urban Dictionary"
+    }
+
+    fn kind(&self) -> EngineKind {
+        EngineKind::PostSearch
+    }
+
+    fn postsearch_request(&self, response: &Response) -> Option<reqwest::RequestBuilder> {
+        request(response)
+    }
+
+    fn postsearch_parse_response(&self, res: &HttpResponse) -> Option<String> {
+        parse_response(&res.body)
+    }
+}
 
 pub fn request(response: &Response) -> Option<reqwest::RequestBuilder> {
+    let re = regex!(r"^https:\/\/www\.urbandictionary\.com\/define\.php\?term=[\w\.\-\+]+$");
     for search_result in response.search_results.iter().take(8) {
-        if regex!(r"^https:\/\/www\.urbandictionary\.com\/define\.php\?term=[\w\.\-\+]+$")
-            .is_match(&search_result.url)
-        {
+        if re.is_match(&search_result.url) {
             return Some(CLIENT.get(search_result.url.as_str()));
         }
     }
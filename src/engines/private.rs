@@ -0,0 +1,212 @@
+//! Engines for search backends we run ourselves, like a Meilisearch index of
+//! our design docs. These are all private, see [`SearchEngine::is_private`].
+
+use std::fmt;
+
+use eyre::bail;
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::custom::parse_engine_url;
+use crate::{
+    engines::{
+        EngineResponse, EngineSearchResult, HttpResponse, RequestResponse, SearchEngine,
+        SearchQuery,
+    },
+    normalize::normalize_url,
+};
+
+pub mod elasticsearch;
+pub mod meilisearch;
+pub mod typesense;
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    Meilisearch,
+    Typesense,
+    Elasticsearch,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PrivateEngineConfig {
+    /// The name shown in the settings, defaults to the id.
+    pub name: Option<String>,
+    pub backend: Backend,
+    /// Where the backend is, like `http://localhost:7700`.
+    pub host: String,
+    /// The index to search, which Typesense calls a collection.
+    pub index: String,
+    pub api_key: Option<String>,
+    #[serde(default = "default_results_per_page")]
+    pub results_per_page: usize,
+    /// Which fields of the documents to use for the results.
+    #[serde(default)]
+    pub fields: FieldMapping,
+}
+
+// not derived so the api key doesn't end up in logs
+impl fmt::Debug for PrivateEngineConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivateEngineConfig")
+            .field("name", &self.name)
+            .field("backend", &self.backend)
+            .field("host", &self.host)
+            .field("index", &self.index)
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("results_per_page", &self.results_per_page)
+            .field("fields", &self.fields)
+            .finish()
+    }
+}
+
+fn default_results_per_page() -> usize {
+    10
+}
+
+/// The names of the document fields that results are made from. Nested
+/// fields can be written like `meta.title`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct FieldMapping {
+    pub url: String,
+    pub title: String,
+    /// The backend gives us a snippet of this field around the words that
+    /// matched with them highlighted, which is used as the description.
+    pub description: String,
+}
+
+impl Default for FieldMapping {
+    fn default() -> Self {
+        Self {
+            url: "url".to_string(),
+            title: "title".to_string(),
+            description: "description".to_string(),
+        }
+    }
+}
+
+pub struct PrivateEngine {
+    id: String,
+    host: Url,
+    config: PrivateEngineConfig,
+}
+
+impl PrivateEngine {
+    pub fn new(id: &str, config: PrivateEngineConfig) -> eyre::Result<Self> {
+        let host = parse_engine_url(&config.host)?;
+        if config.index.is_empty() {
+            bail!("index must not be empty");
+        }
+        if config.results_per_page == 0 {
+            bail!("results_per_page must be greater than 0");
+        }
+
+        Ok(Self {
+            id: id.to_string(),
+            host,
+            config,
+        })
+    }
+
+    /// A url on the backend's host, with the path segments escaped.
+    fn endpoint(&self, path: &[&str]) -> Url {
+        let mut url = self.host.clone();
+        // this can only fail for urls that can't have paths, which http urls
+        // always can
+        url.path_segments_mut().unwrap().pop_if_empty().extend(path);
+        url
+    }
+}
+
+impl SearchEngine for PrivateEngine {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        self.config.name.as_deref().unwrap_or(&self.id)
+    }
+
+    fn is_private(&self) -> bool {
+        true
+    }
+
+    fn supports_time_range(&self) -> bool {
+        false
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        // operators are left out, so a query with only those leaves nothing to
+        // search for
+        let query_string = query.query_for(self);
+        if query_string.is_empty() {
+            return RequestResponse::None;
+        }
+
+        match self.config.backend {
+            Backend::Meilisearch => meilisearch::request(self, query, &query_string),
+            Backend::Typesense => typesense::request(self, query, &query_string),
+            Backend::Elasticsearch => elasticsearch::request(self, query, &query_string),
+        }
+        .into()
+    }
+
+    fn parse_response(&self, res: &HttpResponse) -> eyre::Result<EngineResponse> {
+        let fields = &self.config.fields;
+        let search_results = match self.config.backend {
+            Backend::Meilisearch => meilisearch::parse_response(fields, &res.body),
+            Backend::Typesense => typesense::parse_response(fields, &res.body),
+            Backend::Elasticsearch => elasticsearch::parse_response(fields, &res.body),
+        }?;
+
+        Ok(EngineResponse {
+            search_results,
+            ..Default::default()
+        })
+    }
+}
+
+/// Get a field from a document, either by its full name or by following the
+/// dots in it to a nested field.
+fn document_field<'a>(document: &'a Value, field: &str) -> Option<&'a Value> {
+    if let Some(value) = document.get(field) {
+        return Some(value);
+    }
+    field
+        .split('.')
+        .try_fold(document, |value, key| value.get(key))
+}
+
+fn field_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Make a search result from a document, using the highlighted description if
+/// the backend gave us one. Documents without a url are skipped.
+fn document_to_result(
+    document: &Value,
+    fields: &FieldMapping,
+    highlighted_description: Option<String>,
+) -> Option<EngineSearchResult> {
+    let url = document_field(document, &fields.url).and_then(field_to_string)?;
+    let url = normalize_url(&url).ok()?;
+
+    let field = |field: &str| {
+        document_field(document, field)
+            .and_then(field_to_string)
+            .unwrap_or_default()
+    };
+
+    Some(EngineSearchResult {
+        url,
+        title: field(&fields.title),
+        description: highlighted_description.unwrap_or_else(|| field(&fields.description)),
+    })
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{document_to_result, FieldMapping, PrivateEngine};
+use crate::{
+    engines::{EngineSearchResult, SearchQuery, CLIENT},
+    parse::{HIGHLIGHT_END, HIGHLIGHT_START},
+};
+
+/// How many characters of the description are kept around the match.
+const FRAGMENT_SIZE: usize = 200;
+
+pub fn request(
+    engine: &PrivateEngine,
+    query: &SearchQuery,
+    query_string: &str,
+) -> reqwest::RequestBuilder {
+    let config = &engine.config;
+    let fields = &config.fields;
+
+    let mut request = CLIENT
+        .post(engine.endpoint(&[&config.index, "_search"]))
+        .json(&json!({
+            "from": query.offset(config.results_per_page),
+            "size": config.results_per_page,
+            "query": {
+                "multi_match": {
+                    "query": query_string,
+                    "fields": [fields.title, fields.description],
+                },
+            },
+            "highlight": {
+                "pre_tags": [HIGHLIGHT_START],
+                "post_tags": [HIGHLIGHT_END],
+                "fields": {
+                    &fields.description: {
+                        "fragment_size": FRAGMENT_SIZE,
+                        "number_of_fragments": 1,
+                    },
+                },
+            },
+        }));
+    if let Some(api_key) = &config.api_key {
+        request = request.header("Authorization", format!("ApiKey {api_key}"));
+    }
+    request
+}
+
+#[derive(Deserialize)]
+struct ElasticsearchResponse {
+    hits: ElasticsearchHits,
+}
+
+#[derive(Deserialize)]
+struct ElasticsearchHits {
+    hits: Vec<ElasticsearchHit>,
+}
+
+#[derive(Deserialize)]
+struct ElasticsearchHit {
+    #[serde(rename = "_source")]
+    source: Value,
+    /// The highlighted fragments of each field, keyed by the field's full
+    /// name.
+    #[serde(default)]
+    highlight: HashMap<String, Vec<String>>,
+}
+
+impl ElasticsearchHit {
+    fn fragment(&self, field: &str) -> Option<String> {
+        self.highlight.get(field)?.first().cloned()
+    }
+}
+
+pub fn parse_response(fields: &FieldMapping, body: &str) -> eyre::Result<Vec<EngineSearchResult>> {
+    let response: ElasticsearchResponse = serde_json::from_str(body)?;
+
+    Ok(response
+        .hits
+        .hits
+        .iter()
+        .filter_map(|hit| {
+            document_to_result(&hit.source, fields, hit.fragment(&fields.description))
+        })
+        .collect())
+}
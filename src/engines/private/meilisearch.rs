@@ -0,0 +1,60 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{document_field, document_to_result, FieldMapping, PrivateEngine};
+use crate::{
+    engines::{EngineSearchResult, SearchQuery, CLIENT},
+    parse::{HIGHLIGHT_END, HIGHLIGHT_START},
+};
+
+/// How many words of the description are kept around the match.
+const CROP_LENGTH: usize = 30;
+
+pub fn request(
+    engine: &PrivateEngine,
+    query: &SearchQuery,
+    query_string: &str,
+) -> reqwest::RequestBuilder {
+    let config = &engine.config;
+    let fields = &config.fields;
+
+    let mut request = CLIENT
+        .post(engine.endpoint(&["indexes", &config.index, "search"]))
+        .json(&json!({
+            "q": query_string,
+            "offset": query.offset(config.results_per_page),
+            "limit": config.results_per_page,
+            "attributesToHighlight": [fields.description],
+            "attributesToCrop": [fields.description],
+            "cropLength": CROP_LENGTH,
+            "highlightPreTag": HIGHLIGHT_START,
+            "highlightPostTag": HIGHLIGHT_END,
+        }));
+    if let Some(api_key) = &config.api_key {
+        request = request.bearer_auth(api_key);
+    }
+    request
+}
+
+#[derive(Deserialize)]
+struct MeilisearchResponse {
+    hits: Vec<Value>,
+}
+
+pub fn parse_response(fields: &FieldMapping, body: &str) -> eyre::Result<Vec<EngineSearchResult>> {
+    let response: MeilisearchResponse = serde_json::from_str(body)?;
+
+    Ok(response
+        .hits
+        .iter()
+        .filter_map(|hit| {
+            // _formatted is the document again, but highlighted and cropped
+            let highlighted_description = hit
+                .get("_formatted")
+                .and_then(|formatted| document_field(formatted, &fields.description))
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            document_to_result(hit, fields, highlighted_description)
+        })
+        .collect())
+}
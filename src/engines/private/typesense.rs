@@ -0,0 +1,82 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{document_field, document_to_result, FieldMapping, PrivateEngine};
+use crate::{
+    engines::{EngineSearchResult, SearchQuery, CLIENT},
+    parse::{HIGHLIGHT_END, HIGHLIGHT_START},
+};
+
+pub fn request(
+    engine: &PrivateEngine,
+    query: &SearchQuery,
+    query_string: &str,
+) -> reqwest::RequestBuilder {
+    let config = &engine.config;
+    let fields = &config.fields;
+    let searched_fields = format!("{},{}", fields.title, fields.description);
+
+    let mut request = CLIENT
+        .get(engine.endpoint(&["collections", &config.index, "documents", "search"]))
+        .query(&[
+            ("q", query_string),
+            ("query_by", &searched_fields),
+            ("highlight_fields", &fields.description),
+            ("highlight_start_tag", HIGHLIGHT_START),
+            ("highlight_end_tag", HIGHLIGHT_END),
+            ("page", &query.page.to_string()),
+            ("per_page", &config.results_per_page.to_string()),
+        ]);
+    if let Some(api_key) = &config.api_key {
+        request = request.header("X-TYPESENSE-API-KEY", api_key);
+    }
+    request
+}
+
+#[derive(Deserialize)]
+struct TypesenseResponse {
+    hits: Vec<TypesenseHit>,
+}
+
+#[derive(Deserialize)]
+struct TypesenseHit {
+    document: Value,
+    /// Only in Typesense 0.24 and later, the same shape as the document.
+    #[serde(default)]
+    highlight: Value,
+    #[serde(default)]
+    highlights: Vec<TypesenseHighlight>,
+}
+
+#[derive(Deserialize)]
+struct TypesenseHighlight {
+    field: String,
+    snippet: Option<String>,
+}
+
+impl TypesenseHit {
+    fn snippet(&self, field: &str) -> Option<String> {
+        document_field(&self.highlight, field)
+            .and_then(|highlight| highlight.get("snippet"))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| {
+                self.highlights
+                    .iter()
+                    .find(|highlight| highlight.field == field)
+                    .and_then(|highlight| highlight.snippet.clone())
+            })
+    }
+}
+
+pub fn parse_response(fields: &FieldMapping, body: &str) -> eyre::Result<Vec<EngineSearchResult>> {
+    let response: TypesenseResponse = serde_json::from_str(body)?;
+
+    Ok(response
+        .hits
+        .iter()
+        .filter_map(|hit| {
+            document_to_result(&hit.document, fields, hit.snippet(&fields.description))
+        })
+        .collect())
+}
@@ -0,0 +1,194 @@
+//! The different ways results from several engines can be combined into one
+//! ranking. Which one is used is picked in the config.
+
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+use crate::config;
+
+use super::Engine;
+
+/// Scores a result based on where it was in one engine's results. A result
+/// that several engines returned gets the sum of its scores from each of them.
+pub trait Ranker: Send + Sync {
+    /// `position` starts at 0, and `result_count` is how many results the
+    /// engine returned in total.
+    fn score(&self, engine: Engine, position: usize, result_count: usize) -> f64;
+}
+
+/// Position 1 has a score of 1, position 2 has a score of 0.5, position 3 has
+/// a score of 0.33, etc, multiplied by the engine's weight.
+pub struct ReciprocalRank;
+
+impl Ranker for ReciprocalRank {
+    fn score(&self, engine: Engine, position: usize, _result_count: usize) -> f64 {
+        engine.weight() / (position + 1) as f64
+    }
+}
+
+/// Like [`ReciprocalRank`] but with `k` added to every position, so the top
+/// few results from an engine don't drown out results that several engines
+/// agree on.
+pub struct ReciprocalRankFusion {
+    pub k: f64,
+}
+
+impl Ranker for ReciprocalRankFusion {
+    fn score(&self, engine: Engine, position: usize, _result_count: usize) -> f64 {
+        engine.weight() / (self.k + (position + 1) as f64)
+    }
+}
+
+/// Every engine gives its results points counting down from the number of
+/// results it returned, so the last result gets 1 point.
+pub struct BordaCount;
+
+impl Ranker for BordaCount {
+    fn score(&self, engine: Engine, position: usize, result_count: usize) -> f64 {
+        engine.weight() * result_count.saturating_sub(position) as f64
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(tag = "method", rename_all = "snake_case", deny_unknown_fields)]
+pub enum RankingConfig {
+    #[default]
+    ReciprocalRank,
+    ReciprocalRankFusion {
+        #[serde(default = "default_rrf_k")]
+        k: f64,
+    },
+    BordaCount,
+}
+
+fn default_rrf_k() -> f64 {
+    60.
+}
+
+impl RankingConfig {
+    #[must_use]
+    pub fn ranker(&self) -> Box<dyn Ranker> {
+        match self {
+            Self::ReciprocalRank => Box::new(ReciprocalRank),
+            Self::ReciprocalRankFusion { k } => Box::new(ReciprocalRankFusion { k: *k }),
+            Self::BordaCount => Box::new(BordaCount),
+        }
+    }
+}
+
+static RANKER: Lazy<Box<dyn Ranker>> = Lazy::new(|| config::get().ranking.ranker());
+
+/// The ranker from the config.
+#[must_use]
+pub fn get() -> &'static dyn Ranker {
+    RANKER.as_ref()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use super::*;
+    use crate::engines::{merge_engine_responses, EngineResponse, EngineSearchResult, SearchQuery};
+
+    fn engine(id: &str) -> Engine {
+        Engine::from_id(id).unwrap()
+    }
+
+    fn query() -> SearchQuery {
+        SearchQuery {
+            query: "rust".to_string(),
+            request_headers: HashMap::new(),
+            ip: String::new(),
+            page: 1,
+            locale: Default::default(),
+            safe_search: Default::default(),
+            time_range: None,
+        }
+    }
+
+    fn results(urls: &[&str]) -> EngineResponse {
+        EngineResponse {
+            search_results: urls
+                .iter()
+                .map(|url| EngineSearchResult {
+                    url: url.to_string(),
+                    title: url.to_string(),
+                    description: String::new(),
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reciprocal_rank_fusion_scores() {
+        // bing has a weight of 1
+        let bing = engine("bing");
+        let ranker = ReciprocalRankFusion { k: 60. };
+        assert_eq!(ranker.score(bing, 0, 10), 1. / 61.);
+        assert_eq!(ranker.score(bing, 9, 10), 1. / 70.);
+
+        let ranker = ReciprocalRankFusion { k: 0. };
+        assert_eq!(ranker.score(bing, 1, 10), ReciprocalRank.score(bing, 1, 10));
+
+        // brave has a weight of 1.25
+        let ranker = ReciprocalRankFusion { k: 60. };
+        assert_eq!(ranker.score(engine("brave"), 0, 10), 1.25 / 61.);
+    }
+
+    #[test]
+    fn borda_count_scores() {
+        let bing = engine("bing");
+        assert_eq!(BordaCount.score(bing, 0, 10), 10.);
+        assert_eq!(BordaCount.score(bing, 9, 10), 1.);
+        assert_eq!(BordaCount.score(bing, 10, 10), 0.);
+        assert_eq!(BordaCount.score(engine("brave"), 0, 4), 5.);
+    }
+
+    #[test]
+    fn tied_results_keep_engine_order() {
+        // both engines have a weight of 1, so their top results have the same
+        // score and end up in the order the engines were registered
+        for responses in [
+            [
+                ("bing", "https://b.example/"),
+                ("calc", "https://c.example/"),
+            ],
+            [
+                ("calc", "https://c.example/"),
+                ("bing", "https://b.example/"),
+            ],
+        ] {
+            let responses = responses
+                .iter()
+                .map(|(id, url)| (engine(id), results(&[url])))
+                .collect::<BTreeMap<_, _>>();
+            let response = merge_engine_responses(&query(), responses, false);
+            let urls = response
+                .search_results
+                .iter()
+                .map(|result| result.url.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(urls, vec!["https://b.example/", "https://c.example/"]);
+        }
+    }
+
+    #[test]
+    fn tied_answers_go_to_first_engine() {
+        // calc and wikipedia have the same weight, calc is registered first
+        for ids in [["calc", "wikipedia"], ["wikipedia", "calc"]] {
+            let responses = ids
+                .iter()
+                .map(|id| {
+                    let mut response = EngineResponse::answer_html(id.to_string());
+                    response.infobox_html = Some(id.to_string());
+                    (engine(id), response)
+                })
+                .collect::<BTreeMap<_, _>>();
+            let response = merge_engine_responses(&query(), responses, false);
+            assert_eq!(response.answer.unwrap().engine, engine("calc"));
+            assert_eq!(response.infobox.unwrap().engine, engine("calc"));
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! The [`SearchEngine`] trait and the registry of every engine we know about.
+//! The built-in engines are always registered first, and more can be added
+//! with [`register`] before the first search.
+
+use std::{fmt, ops::Deref, sync::Mutex};
+
+use eyre::bail;
+use once_cell::sync::Lazy;
+use reqwest::StatusCode;
+
+use super::{
+    answer, operators::OperatorSupport, postsearch, search, EngineResponse, HttpResponse,
+    RequestAutocompleteResponse, RequestResponse, Response, SearchQuery,
+};
+
+/// What an engine gives us, which decides where it's used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineKind {
+    /// Gives search results, and is what `@engine` selectors pick between.
+    Search,
+    /// Gives instant answers and infoboxes for the query.
+    Answer,
+    /// Gives an infobox for one of the merged results.
+    PostSearch,
+}
+
+/// Something that can be searched. Everything other than [`SearchEngine::id`]
+/// and [`SearchEngine::name`] has a default, so an engine only has to
+/// implement the parts it uses.
+///
+/// Normal search engines implement [`SearchEngine::request`] and
+/// [`SearchEngine::parse_response`], answer engines usually return
+/// [`RequestResponse::Instant`] from `request` instead, and post-search engines
+/// implement [`SearchEngine::postsearch_request`] and
+/// [`SearchEngine::postsearch_parse_response`].
+pub trait SearchEngine: Send + Sync {
+    /// The id used in the config, settings and `@engine` selectors, like
+    /// `google`. This has to be unique.
+    fn id(&self) -> &str;
+
+    /// The name shown to people, like `Google`.
+    fn name(&self) -> &str;
+
+    /// Most engines are search engines, answer and post-search engines have to
+    /// say that they aren't.
+    fn kind(&self) -> EngineKind {
+        EngineKind::Search
+    }
+
+    /// The weight this engine has when it isn't overridden in the config.
+    fn default_weight(&self) -> f64 {
+        1.
+    }
+
+    /// Whether this engine is scholarly when it isn't overridden in the config.
+    fn default_scholarly(&self) -> bool {
+        false
+    }
+
+    /// Whether this engine is enabled by default when it isn't overridden in
+    /// the config.
+    fn default_enabled(&self) -> bool {
+        true
+    }
+
+    /// Whether this engine can limit its results to a time range. Engines that
+    /// can't are skipped when a time range is picked.
+    fn supports_time_range(&self) -> bool {
+        true
+    }
+
+    /// Private engines search our own data instead of the public web. They're
+    /// weighted with `search.private_engine_weight` unless the config says
+    /// otherwise, and what only they found is never given to post-search
+    /// engines, since those fetch urls from the internet.
+    fn is_private(&self) -> bool {
+        false
+    }
+
+    /// The search operators this engine understands, the others are checked on
+    /// its results instead.
+    fn operators(&self) -> OperatorSupport {
+        OperatorSupport::NONE
+    }
+
+    fn request(&self, _query: &SearchQuery) -> RequestResponse {
+        RequestResponse::None
+    }
+
+    /// Whether a response with this status gets parsed. Other statuses count
+    /// as the engine failing.
+    fn accepts_status(&self, status: StatusCode) -> bool {
+        status.is_success()
+    }
+
+    fn parse_response(&self, _res: &HttpResponse) -> eyre::Result<EngineResponse> {
+        bail!("engine {} can't parse response", self.id())
+    }
+
+    fn request_autocomplete(&self, _query: &SearchQuery) -> Option<RequestAutocompleteResponse> {
+        None
+    }
+
+    fn parse_autocomplete_response(&self, _body: &str) -> eyre::Result<Vec<String>> {
+        bail!("engine {} can't parse autocomplete response", self.id())
+    }
+
+    /// Post-search engines are requested after the main results are merged,
+    /// and only if there isn't already an infobox.
+    fn postsearch_request(&self, _response: &Response) -> Option<reqwest::RequestBuilder> {
+        None
+    }
+
+    fn postsearch_parse_response(&self, _res: &HttpResponse) -> Option<String> {
+        None
+    }
+}
+
+fn builtin_engines() -> Vec<Box<dyn SearchEngine>> {
+    vec![
+        // search
+        Box::new(search::google::Google),
+        Box::new(search::bing::Bing),
+        Box::new(search::brave::Brave),
+        Box::new(search::marginalia::Marginalia),
+        Box::new(search::google_scholar::GoogleScholar),
+        Box::new(search::stract::Stract),
+        Box::new(search::rightdao::Rightdao),
+        Box::new(search::yep::Yep),
+        // answer
+        Box::new(answer::useragent::Useragent),
+        Box::new(answer::ip::Ip),
+        Box::new(answer::calc::Calc),
+        Box::new(answer::wikipedia::Wikipedia),
+        Box::new(answer::dictionary::Dictionary),
+        Box::new(answer::timezone::Timezone),
+        Box::new(answer::notepad::Notepad),
+        Box::new(answer::random::Random),
+        // post-search
+        Box::new(postsearch::stackexchange::StackExchange),
+        Box::new(postsearch::github::GitHub),
+        Box::new(postsearch::docs_rs::DocsRs),
+        Box::new(postsearch::urbandictionary::UrbanDictionary),
+    ]
+}
+
+/// Engines registered with [`register`] that haven't been added to the
+/// registry yet.
+struct Pending {
+    engines: Vec<Box<dyn SearchEngine>>,
+    /// Set once the registry is built, after which nothing else can be
+    /// registered.
+    frozen: bool,
+}
+
+static PENDING: Mutex<Pending> = Mutex::new(Pending {
+    engines: Vec::new(),
+    frozen: false,
+});
+
+struct Registry {
+    engines: Vec<&'static dyn SearchEngine>,
+    handles: Vec<Engine>,
+}
+
+static REGISTRY: Lazy<Registry> = Lazy::new(|| {
+    let mut pending = PENDING.lock().unwrap();
+    pending.frozen = true;
+
+    let mut engines = builtin_engines();
+    engines.append(&mut pending.engines);
+    // the registry lives for the whole program, so leaking the engines lets
+    // us hand out 'static references to them
+    let engines = engines
+        .into_iter()
+        .map(|engine| &*Box::leak(engine))
+        .collect::<Vec<_>>();
+    let handles = (0..engines.len()).map(Engine).collect();
+    Registry { engines, handles }
+});
+
+/// Add an engine to the registry. This has to be done before
+/// [`Config::register_engines`](crate::config::Config::register_engines),
+/// since anything that looks up an engine builds the registry and stops any
+/// more from being added.
+pub fn register(engine: impl SearchEngine + 'static) -> eyre::Result<()> {
+    let mut pending = PENDING.lock().unwrap();
+    if pending.frozen {
+        bail!(
+            "engine '{}' was registered after the engine registry was built",
+            engine.id()
+        );
+    }
+    let is_duplicate = builtin_engines()
+        .iter()
+        .chain(&pending.engines)
+        .any(|existing| existing.id() == engine.id());
+    if is_duplicate {
+        bail!("an engine with the id '{}' already exists", engine.id());
+    }
+    pending.engines.push(Box::new(engine));
+    Ok(())
+}
+
+/// A handle to an engine in the registry. These are cheap to copy and compare,
+/// and they're ordered the same way the engines were registered. Everything
+/// from [`SearchEngine`] can be called on it directly.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Engine(usize);
+
+impl Engine {
+    /// Every registered engine, with the built-in ones first.
+    #[must_use]
+    pub fn all() -> &'static [Engine] {
+        &REGISTRY.handles
+    }
+
+    #[must_use]
+    pub fn from_id(id: &str) -> Option<Engine> {
+        Self::all().iter().copied().find(|engine| engine.id() == id)
+    }
+
+    /// The engine this is a handle to.
+    #[must_use]
+    pub fn get(self) -> &'static dyn SearchEngine {
+        REGISTRY.engines[self.0]
+    }
+
+    #[must_use]
+    pub fn id(&self) -> &'static str {
+        self.get().id()
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        self.get().name()
+    }
+}
+
+impl Deref for Engine {
+    type Target = dyn SearchEngine;
+
+    fn deref(&self) -> &Self::Target {
+        self.get()
+    }
+}
+
+impl fmt::Debug for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Engine({})", self.id())
+    }
+}
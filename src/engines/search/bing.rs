@@ -1,21 +1,76 @@
-use base64::Engine;
+use base64::Engine as _;
 use scraper::{ElementRef, Selector};
 use url::Url;
 
 use crate::{
-    engines::{EngineResponse, CLIENT},
+    engines::{
+        operators::OperatorSupport, EngineResponse, HttpResponse, RequestResponse, SearchEngine,
+        SearchQuery, TimeRange, CLIENT,
+    },
     parse::{parse_html_response_with_opts, ParseOpts, QueryMethod},
 };
 
-pub fn request(query: &str) -> reqwest::RequestBuilder {
-    CLIENT.get(
-        Url::parse_with_params(
-            "https://www.bing.com/search",
-            // filters=rcrse:"1" makes it not try to autocorrect
-            &[("q", query), ("filters", "rcrse:\"1\"")],
-        )
-        .unwrap(),
-    )
+pub struct Bing;
+
+impl SearchEngine for Bing {
+    fn id(&self) -> &str {
+        "bing"
+    }
+
+    fn name(&self) -> &str {
+        "Bing"
+    }
+
+    fn default_weight(&self) -> f64 {
+        1.
+    }
+
+    fn operators(&self) -> OperatorSupport {
+        OperatorSupport::ALL
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        request(query).into()
+    }
+
+    fn parse_response(&self, res: &HttpResponse) -> eyre::Result<EngineResponse> {
+        parse_response(&res.body)
+    }
+}
+
+pub fn request(query: &SearchQuery) -> reqwest::RequestBuilder {
+    // filters=rcrse:"1" makes it not try to autocorrect
+    let mut filters = "rcrse:\"1\"".to_string();
+    if let Some(time_range) = query.time_range {
+        let ex1 = match time_range {
+            TimeRange::Day => "ez1".to_string(),
+            TimeRange::Week => "ez2".to_string(),
+            TimeRange::Month => "ez3".to_string(),
+            // bing doesn't have a preset for a year, so we give it a custom
+            // range in days since the unix epoch
+            TimeRange::Year => {
+                let today = chrono::Utc::now().timestamp() / 86400;
+                format!("ez5_{}_{today}", today - 365)
+            }
+        };
+        filters.push_str(&format!(" ex1:\"{ex1}\""));
+    }
+
+    let mut params = vec![
+        ("q", query.query_for(&Bing)),
+        ("filters", filters),
+        ("setlang", query.locale.language.clone()),
+        ("adlt", query.safe_search.to_string()),
+    ];
+    if let Some(region) = &query.locale.region {
+        params.push(("cc", region.clone()));
+    }
+    if query.page > 1 {
+        // bing's results are 1-indexed
+        params.push(("first", (query.offset(10) + 1).to_string()));
+    }
+
+    CLIENT.get(Url::parse_with_params("https://www.bing.com/search", &params).unwrap())
 }
 
 pub fn parse_response(body: &str) -> eyre::Result<EngineResponse> {
@@ -47,13 +102,14 @@ pub fn parse_response(body: &str) -> eyre::Result<EngineResponse> {
                         scraper::Node::Text(t) => {
                             description.push_str(&t.text);
                         }
-                        scraper::Node::Element(inner_el) => {
-                            if !inner_el
-                                .has_class("algoSlug_icon", scraper::CaseSensitivity::CaseSensitive)
-                            {
-                                let element_ref = ElementRef::wrap(inner_node).unwrap();
-                                description.push_str(&element_ref.text().collect::<String>());
-                            }
+                        scraper::Node::Element(inner_el)
+                            if !inner_el.has_class(
+                                "algoSlug_icon",
+                                scraper::CaseSensitivity::CaseSensitive,
+                            ) =>
+                        {
+                            let element_ref = ElementRef::wrap(inner_node).unwrap();
+                            description.push_str(&element_ref.text().collect::<String>());
                         }
                         _ => {}
                     }
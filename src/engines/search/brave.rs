@@ -1,12 +1,64 @@
 use url::Url;
 
 use crate::{
-    engines::{EngineResponse, CLIENT},
+    engines::{
+        operators::OperatorSupport, EngineResponse, HttpResponse, RequestResponse, SearchEngine,
+        SearchQuery, TimeRange, CLIENT,
+    },
     parse::{parse_html_response_with_opts, ParseOpts},
 };
 
-pub fn request(query: &str) -> reqwest::RequestBuilder {
-    CLIENT.get(Url::parse_with_params("https://search.brave.com/search", &[("q", query)]).unwrap())
+pub struct Brave;
+
+impl SearchEngine for Brave {
+    fn id(&self) -> &str {
+        "brave"
+    }
+
+    fn name(&self) -> &str {
+        "Brave"
+    }
+
+    fn default_weight(&self) -> f64 {
+        1.25
+    }
+
+    fn operators(&self) -> OperatorSupport {
+        OperatorSupport::ALL
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        request(query).into()
+    }
+
+    fn parse_response(&self, res: &HttpResponse) -> eyre::Result<EngineResponse> {
+        parse_response(&res.body)
+    }
+}
+
+pub fn request(query: &SearchQuery) -> reqwest::RequestBuilder {
+    let mut params = vec![
+        ("q", query.query_for(&Brave)),
+        ("safesearch", query.safe_search.to_string()),
+    ];
+    if let Some(region) = query.locale.region_lowercase() {
+        params.push(("country", region));
+    }
+    if let Some(time_range) = query.time_range {
+        let tf = match time_range {
+            TimeRange::Day => "pd",
+            TimeRange::Week => "pw",
+            TimeRange::Month => "pm",
+            TimeRange::Year => "py",
+        };
+        params.push(("tf", tf.to_string()));
+    }
+    if query.page > 1 {
+        // brave's offset is the page index, not the result index
+        params.push(("offset", (query.page - 1).to_string()));
+    }
+
+    CLIENT.get(Url::parse_with_params("https://search.brave.com/search", &params).unwrap())
 }
 
 pub fn parse_response(body: &str) -> eyre::Result<EngineResponse> {
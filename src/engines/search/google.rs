@@ -2,19 +2,80 @@ use scraper::{ElementRef, Selector};
 use url::Url;
 
 use crate::{
-    engines::{EngineResponse, CLIENT},
+    engines::{
+        operators::OperatorSupport, EngineResponse, HttpResponse, RequestAutocompleteResponse,
+        RequestResponse, SafeSearch, SearchEngine, SearchQuery, TimeRange, CLIENT,
+    },
     parse::{parse_html_response_with_opts, ParseOpts, QueryMethod},
 };
 
-pub fn request(query: &str) -> reqwest::RequestBuilder {
-    CLIENT.get(
-        Url::parse_with_params(
-            "https://www.google.com/search",
-            // nfpr makes it not try to autocorrect
-            &[("q", query), ("nfpr", "1")],
-        )
-        .unwrap(),
-    )
+pub struct Google;
+
+impl SearchEngine for Google {
+    fn id(&self) -> &str {
+        "google"
+    }
+
+    fn name(&self) -> &str {
+        "Google"
+    }
+
+    fn default_weight(&self) -> f64 {
+        1.05
+    }
+
+    fn operators(&self) -> OperatorSupport {
+        OperatorSupport::ALL
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        request(query).into()
+    }
+
+    fn parse_response(&self, res: &HttpResponse) -> eyre::Result<EngineResponse> {
+        parse_response(&res.body)
+    }
+
+    fn request_autocomplete(&self, query: &SearchQuery) -> Option<RequestAutocompleteResponse> {
+        Some(request_autocomplete(query).into())
+    }
+
+    fn parse_autocomplete_response(&self, body: &str) -> eyre::Result<Vec<String>> {
+        parse_autocomplete_response(body)
+    }
+}
+
+pub fn request(query: &SearchQuery) -> reqwest::RequestBuilder {
+    // nfpr makes it not try to autocorrect
+    let mut params = vec![
+        ("q", query.query_for(&Google)),
+        ("nfpr", "1".to_string()),
+        ("hl", query.locale.language.clone()),
+    ];
+    if let Some(region) = &query.locale.region {
+        params.push(("gl", region.clone()));
+    }
+    // google doesn't have a moderate level, it just blurs explicit images by
+    // default
+    match query.safe_search {
+        SafeSearch::Off => params.push(("safe", "off".to_string())),
+        SafeSearch::Moderate => {}
+        SafeSearch::Strict => params.push(("safe", "active".to_string())),
+    }
+    if let Some(time_range) = query.time_range {
+        let qdr = match time_range {
+            TimeRange::Day => "d",
+            TimeRange::Week => "w",
+            TimeRange::Month => "m",
+            TimeRange::Year => "y",
+        };
+        params.push(("tbs", format!("qdr:{qdr}")));
+    }
+    if query.page > 1 {
+        params.push(("start", query.offset(10).to_string()));
+    }
+
+    CLIENT.get(Url::parse_with_params("https://www.google.com/search", &params).unwrap())
 }
 
 pub fn parse_response(body: &str) -> eyre::Result<EngineResponse> {
@@ -67,33 +128,31 @@ fn iter_featured_snippet_children(description: &mut String, el: &ElementRef) {
             scraper::Node::Text(t) => {
                 description.push_str(&t.text);
             }
-            scraper::Node::Element(inner_el) => {
+            scraper::Node::Element(inner_el)
                 if inner_el.attr("data-ved").is_none()
-                    || inner_el.attr("data-send-open-event").is_some()
-                {
-                    iter_featured_snippet_children(
-                        description,
-                        &ElementRef::wrap(inner_node).unwrap(),
-                    );
-                }
+                    || inner_el.attr("data-send-open-event").is_some() =>
+            {
+                iter_featured_snippet_children(description, &ElementRef::wrap(inner_node).unwrap());
             }
             _ => {}
         }
     }
 }
 
-pub fn request_autocomplete(query: &str) -> reqwest::RequestBuilder {
+pub fn request_autocomplete(query: &SearchQuery) -> reqwest::RequestBuilder {
+    let mut params = vec![
+        ("output", "firefox"),
+        ("client", "firefox"),
+        ("hl", query.locale.language.as_str()),
+        ("q", query.query.as_str()),
+    ];
+    if let Some(region) = &query.locale.region {
+        params.push(("gl", region));
+    }
+
     CLIENT.get(
-        Url::parse_with_params(
-            "https://suggestqueries.google.com/complete/search",
-            &[
-                ("output", "firefox"),
-                ("client", "firefox"),
-                ("hl", "US-en"),
-                ("q", query),
-            ],
-        )
-        .unwrap(),
+        Url::parse_with_params("https://suggestqueries.google.com/complete/search", &params)
+            .unwrap(),
     )
 }
 
@@ -1,19 +1,72 @@
 use reqwest::Url;
 
 use crate::{
-    engines::{EngineResponse, RequestResponse, CLIENT},
+    engines::{
+        operators::OperatorSupport, EngineResponse, HttpResponse, RequestResponse, SearchEngine,
+        SearchQuery, CLIENT,
+    },
     parse::{parse_html_response_with_opts, ParseOpts},
 };
 
-pub fn request(query: &str) -> RequestResponse {
+pub struct GoogleScholar;
+
+impl SearchEngine for GoogleScholar {
+    fn id(&self) -> &str {
+        "scholar"
+    }
+
+    fn name(&self) -> &str {
+        "Google Scholar"
+    }
+
+    fn default_weight(&self) -> f64 {
+        2.
+    }
+
+    fn default_scholarly(&self) -> bool {
+        true
+    }
+
+    fn supports_time_range(&self) -> bool {
+        false
+    }
+
+    fn operators(&self) -> OperatorSupport {
+        OperatorSupport {
+            filetype: false,
+            ..OperatorSupport::ALL
+        }
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        request(query)
+    }
+
+    fn parse_response(&self, res: &HttpResponse) -> eyre::Result<EngineResponse> {
+        parse_response(&res.body)
+    }
+}
+
+pub fn request(query: &SearchQuery) -> RequestResponse {
+    // filetype: is left out, so a query with only that leaves nothing to
+    // search for
+    let query_string = query.query_for(&GoogleScholar);
+    if query_string.is_empty() {
+        return RequestResponse::None;
+    }
+
+    let mut params = vec![
+        ("hl", query.locale.language.clone()),
+        ("as_sdt", "0,5".to_string()),
+        ("q", query_string),
+        ("btnG", String::new()),
+    ];
+    if query.page > 1 {
+        params.push(("start", query.offset(10).to_string()));
+    }
+
     CLIENT
-        .get(
-            Url::parse_with_params(
-                "https://scholar.google.com/scholar",
-                &[("hl", "en"), ("as_sdt", "0,5"), ("q", query), ("btnG", "")],
-            )
-            .unwrap(),
-        )
+        .get(Url::parse_with_params("https://scholar.google.com/scholar", &params).unwrap())
         .into()
 }
 
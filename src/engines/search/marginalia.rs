@@ -1,14 +1,48 @@
 use reqwest::Url;
 
 use crate::{
-    engines::{EngineResponse, RequestResponse, CLIENT},
+    engines::{EngineResponse, HttpResponse, RequestResponse, SearchEngine, SearchQuery, CLIENT},
     parse::{parse_html_response_with_opts, ParseOpts},
 };
 
-pub fn request(query: &str) -> RequestResponse {
-    // if the query is more than 3 words or has any special characters then abort
-    if query.split_whitespace().count() > 3
-        || !query.chars().all(|c| c.is_ascii_alphanumeric() || c == ' ')
+pub struct Marginalia;
+
+impl SearchEngine for Marginalia {
+    fn id(&self) -> &str {
+        "marginalia"
+    }
+
+    fn name(&self) -> &str {
+        "Marginalia"
+    }
+
+    fn default_weight(&self) -> f64 {
+        0.15
+    }
+
+    fn supports_time_range(&self) -> bool {
+        false
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        request(query)
+    }
+
+    fn parse_response(&self, res: &HttpResponse) -> eyre::Result<EngineResponse> {
+        parse_response(&res.body)
+    }
+}
+
+pub fn request(query: &SearchQuery) -> RequestResponse {
+    // operators are checked on the results afterwards, so only the terms are
+    // sent. if there aren't any, or there are more than 3 words or any special
+    // characters, then abort
+    let query_string = query.query_for(&Marginalia);
+    if query_string.is_empty()
+        || query_string.split_whitespace().count() > 3
+        || !query_string
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == ' ')
     {
         return RequestResponse::None;
     }
@@ -18,10 +52,11 @@ pub fn request(query: &str) -> RequestResponse {
             Url::parse_with_params(
                 "https://search.marginalia.nu/search",
                 &[
-                    ("query", query),
+                    ("query", query_string.as_str()),
                     ("profile", "corpo"),
                     ("js", "default"),
                     ("adtech", "default"),
+                    ("page", &query.page.to_string()),
                 ],
             )
             .unwrap(),
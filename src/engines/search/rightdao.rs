@@ -1,13 +1,57 @@
 use reqwest::Url;
 
 use crate::{
-    engines::{EngineResponse, RequestResponse, CLIENT},
+    engines::{EngineResponse, HttpResponse, RequestResponse, SearchEngine, SearchQuery, CLIENT},
     parse::{parse_html_response_with_opts, ParseOpts},
 };
 
-pub fn request(query: &str) -> RequestResponse {
+pub struct Rightdao;
+
+impl SearchEngine for Rightdao {
+    fn id(&self) -> &str {
+        "rightdao"
+    }
+
+    fn name(&self) -> &str {
+        "Right Dao"
+    }
+
+    fn default_weight(&self) -> f64 {
+        0.10
+    }
+
+    fn default_enabled(&self) -> bool {
+        false
+    }
+
+    fn supports_time_range(&self) -> bool {
+        false
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        request(query)
+    }
+
+    fn parse_response(&self, res: &HttpResponse) -> eyre::Result<EngineResponse> {
+        parse_response(&res.body)
+    }
+}
+
+pub fn request(query: &SearchQuery) -> RequestResponse {
+    // rightdao doesn't have pagination
+    if query.page > 1 {
+        return RequestResponse::None;
+    }
+
+    // operators are left out, so a query with only those leaves nothing to
+    // search for
+    let query_string = query.query_for(&Rightdao);
+    if query_string.is_empty() {
+        return RequestResponse::None;
+    }
+
     CLIENT
-        .get(Url::parse_with_params("https://rightdao.com/search", &[("q", query)]).unwrap())
+        .get(Url::parse_with_params("https://rightdao.com/search", &[("q", query_string)]).unwrap())
         .into()
 }
 
@@ -1,11 +1,62 @@
 use reqwest::Url;
 
 use crate::{
-    engines::{EngineResponse, RequestResponse, CLIENT},
+    engines::{
+        operators::OperatorSupport, EngineResponse, HttpResponse, RequestResponse, SearchEngine,
+        SearchQuery, CLIENT,
+    },
     parse::{parse_html_response_with_opts, ParseOpts},
 };
 
-pub fn request(query: &str) -> RequestResponse {
+pub struct Stract;
+
+impl SearchEngine for Stract {
+    fn id(&self) -> &str {
+        "stract"
+    }
+
+    fn name(&self) -> &str {
+        "Stract"
+    }
+
+    fn default_weight(&self) -> f64 {
+        0.15
+    }
+
+    fn default_enabled(&self) -> bool {
+        false
+    }
+
+    fn supports_time_range(&self) -> bool {
+        false
+    }
+
+    fn operators(&self) -> OperatorSupport {
+        OperatorSupport {
+            phrases: true,
+            exclusions: true,
+            site: true,
+            ..OperatorSupport::NONE
+        }
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        request(query)
+    }
+
+    fn parse_response(&self, res: &HttpResponse) -> eyre::Result<EngineResponse> {
+        parse_response(&res.body)
+    }
+}
+
+pub fn request(query: &SearchQuery) -> RequestResponse {
+    let query_string = query.query_for(&Stract);
+    // operators it doesn't support are left out, so a query with only those
+    // leaves nothing to search for
+    if query_string.is_empty() {
+        return RequestResponse::None;
+    }
+
     CLIENT
         .get(
             Url::parse_with_params(
@@ -13,8 +64,10 @@ pub fn request(query: &str) -> RequestResponse {
                 &[
                     ("ss", "false"),
                     ("sr", "N4IgNglg1gpgJiAXAbQLoBoRwgZ0rBFDEAIzAHsBjApNAXyA"),
-                    ("q", query),
+                    ("q", query_string.as_str()),
                     ("optic", ""),
+                    // stract's pages start at 0
+                    ("p", &(query.page - 1).to_string()),
                 ],
             )
             .unwrap(),
@@ -1,19 +1,66 @@
 use reqwest::Url;
 use serde::Deserialize;
 
-use crate::engines::{EngineResponse, EngineSearchResult, RequestResponse, CLIENT};
+use crate::engines::{
+    EngineResponse, EngineSearchResult, HttpResponse, RequestResponse, SearchEngine, SearchQuery,
+    CLIENT,
+};
+
+pub struct Yep;
+
+impl SearchEngine for Yep {
+    fn id(&self) -> &str {
+        "yep"
+    }
+
+    fn name(&self) -> &str {
+        "Yep"
+    }
+
+    fn default_weight(&self) -> f64 {
+        0.10
+    }
+
+    fn default_enabled(&self) -> bool {
+        false
+    }
+
+    fn supports_time_range(&self) -> bool {
+        false
+    }
+
+    fn request(&self, query: &SearchQuery) -> RequestResponse {
+        request(query)
+    }
+
+    fn parse_response(&self, res: &HttpResponse) -> eyre::Result<EngineResponse> {
+        parse_response(&res.body)
+    }
+}
+
+pub fn request(query: &SearchQuery) -> RequestResponse {
+    let query_string = query.query_for(&Yep);
+    // operators are left out, so a query with only those leaves nothing to
+    // search for
+    if query_string.is_empty() {
+        return RequestResponse::None;
+    }
+    let region = query
+        .locale
+        .region_lowercase()
+        .unwrap_or_else(|| "all".to_string());
 
-pub fn request(query: &str) -> RequestResponse {
     CLIENT
         .get(
             Url::parse_with_params(
                 "https://api.yep.com/fs/2/search",
                 &[
                     ("client", "web"),
-                    ("gl", "all"),
+                    ("gl", region.as_str()),
                     ("no_correct", "true"),
-                    ("q", query),
-                    ("safeSearch", "off"),
+                    ("offset", &query.offset(10).to_string()),
+                    ("q", query_string.as_str()),
+                    ("safeSearch", query.safe_search.as_str()),
                     ("type", "web"),
                 ],
             )
@@ -53,6 +100,7 @@ pub fn parse_response(body: &str) -> eyre::Result<EngineResponse> {
         featured_snippet: None,
         answer_html: None,
         infobox_html: None,
+        filtered_results: Vec::new(),
     };
     Ok(engine_response)
 }
@@ -0,0 +1,75 @@
+//! Picking engines from inside the query, like `@google @brave rust lifetimes`
+//! to only search Google and Brave, or `+scholar` to include scholarly engines.
+//! These only last for the one search, unlike the engine settings.
+
+use super::Engine;
+
+#[derive(Debug, Default)]
+pub struct EngineSelectors {
+    /// The engines picked with `@engine`, which replace the other search
+    /// engines. If this is empty then every engine is used like normal.
+    pub engines: Vec<Engine>,
+    /// Whether `+scholar` was in the query.
+    pub force_scholarly: bool,
+}
+
+impl EngineSelectors {
+    /// Take the selectors out of the query, returning the query without them.
+    /// Anything that looks like a selector but isn't one (like `@types/node`)
+    /// is left in the query.
+    #[must_use]
+    pub fn extract(query: &str) -> (String, Self) {
+        let mut selectors = Self::default();
+        let mut words = Vec::new();
+
+        for word in query.split_whitespace() {
+            if let Some(engine) = word
+                .strip_prefix('@')
+                .and_then(|id| Engine::from_id(&id.to_lowercase()))
+            {
+                if !selectors.engines.contains(&engine) {
+                    selectors.engines.push(engine);
+                }
+            } else if matches!(word.to_lowercase().as_str(), "+scholar" | "+scholarly") {
+                selectors.force_scholarly = true;
+            } else {
+                words.push(word);
+            }
+        }
+
+        (words.join(" "), selectors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine(id: &str) -> Engine {
+        Engine::from_id(id).unwrap()
+    }
+
+    #[test]
+    fn extract_engines() {
+        let (query, selectors) = EngineSelectors::extract("@google rust @Brave lifetimes @google");
+        assert_eq!(query, "rust lifetimes");
+        assert_eq!(selectors.engines, vec![engine("google"), engine("brave")]);
+        assert!(!selectors.force_scholarly);
+    }
+
+    #[test]
+    fn extract_scholarly() {
+        let (query, selectors) = EngineSelectors::extract("+Scholar borrow checker");
+        assert_eq!(query, "borrow checker");
+        assert!(selectors.engines.is_empty());
+        assert!(selectors.force_scholarly);
+    }
+
+    #[test]
+    fn non_selectors_stay_in_query() {
+        let (query, selectors) = EngineSelectors::extract("@types/node @notanengine c++ +1");
+        assert_eq!(query, "@types/node @notanengine c++ +1");
+        assert!(selectors.engines.is_empty());
+        assert!(!selectors.force_scholarly);
+    }
+}
@@ -0,0 +1,12 @@
+//! metasearch2 can also be used as a library, mostly so other crates can add
+//! their own engines with [`engines::register`] before starting the server
+//! like `main.rs` does.
+
+pub mod bangs;
+pub mod cli;
+pub mod config;
+pub mod engines;
+pub mod locale;
+pub mod normalize;
+pub mod parse;
+pub mod web;
@@ -0,0 +1,88 @@
+//! The language and region that a search is made in.
+
+use std::fmt;
+
+/// A language and optionally a region, like `en-US` or `de`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Locale {
+    /// A lowercase ISO 639 language code, like `en`.
+    pub language: String,
+    /// An uppercase ISO 3166 region code, like `US`.
+    pub region: Option<String>,
+}
+
+impl Locale {
+    /// Parse a locale like `en-US`, `en_us` or `de`. Script subtags (like the
+    /// `Hant` in `zh-Hant-TW`) are ignored.
+    #[must_use]
+    pub fn parse(locale: &str) -> Option<Self> {
+        let mut subtags = locale.trim().split(['-', '_']);
+
+        let language = subtags.next()?;
+        if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic())
+        {
+            return None;
+        }
+
+        let mut region = None;
+        for subtag in subtags {
+            let is_region = (subtag.len() == 2 && subtag.chars().all(|c| c.is_ascii_alphabetic()))
+                || (subtag.len() == 3 && subtag.chars().all(|c| c.is_ascii_digit()));
+            if is_region {
+                region = Some(subtag.to_ascii_uppercase());
+                break;
+            }
+        }
+
+        Some(Self {
+            language: language.to_ascii_lowercase(),
+            region,
+        })
+    }
+
+    /// Get the locale the browser prefers from its Accept-Language header, like
+    /// `de-DE,de;q=0.9,en;q=0.8`. The languages are usually already sorted by
+    /// preference so we just take the first valid one.
+    #[must_use]
+    pub fn from_accept_language(header: &str) -> Option<Self> {
+        header
+            .split(',')
+            .map(|language| language.split(';').next().unwrap_or_default())
+            .find_map(Self::parse)
+    }
+
+    /// The value we send in the Accept-Language header to upstream engines.
+    #[must_use]
+    pub fn accept_language(&self) -> String {
+        match &self.region {
+            Some(_) => format!("{self},{};q=0.5", self.language),
+            None => self.language.clone(),
+        }
+    }
+
+    /// The region as a lowercase code, since some engines want it like that.
+    #[must_use]
+    pub fn region_lowercase(&self) -> Option<String> {
+        self.region
+            .as_ref()
+            .map(|region| region.to_ascii_lowercase())
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self {
+            language: "en".to_string(),
+            region: Some("US".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.region {
+            Some(region) => write!(f, "{}-{region}", self.language),
+            None => write!(f, "{}", self.language),
+        }
+    }
+}
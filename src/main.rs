@@ -1,11 +1,30 @@
-pub mod engines;
-pub mod normalize;
-pub mod parse;
-pub mod web;
+use clap::Parser;
+use metasearch2::{cli, config, web};
 
 #[tokio::main(flavor = "current_thread")]
-async fn main() {
-    simple_logger::init_with_level(log::Level::Info).unwrap();
+async fn main() -> eyre::Result<()> {
+    let args = cli::Cli::parse();
+
+    // logs go to stderr, but keep them quiet in query mode so they don't drown
+    // out the results
+    let log_level = match args.command {
+        Some(cli::Command::Query { .. }) => log::Level::Warn,
+        _ => log::Level::Info,
+    };
+    simple_logger::SimpleLogger::new()
+        .with_level(log_level.to_level_filter())
+        // tantivy logs every commit of the local index
+        .with_module_level("tantivy", log::LevelFilter::Warn)
+        .init()
+        .unwrap();
+
+    let mut config = config::load(args.config.as_deref())?;
+    if let Some(cli::Command::Serve { bind: Some(bind) }) = &args.command {
+        config.bind = bind.clone();
+        config.validate()?;
+    }
+    config.register_engines()?;
+    config::set(config);
 
     unsafe {
         const ADDITIONAL_PROFANE_WORDS: &[&str] =
@@ -19,5 +38,31 @@ async fn main() {
         }
     }
 
-    web::run().await;
+    match args.command {
+        None | Some(cli::Command::Serve { .. }) => web::run().await,
+        Some(cli::Command::Query {
+            query,
+            scholarly,
+            page,
+            locale,
+            safe_search,
+            time_range,
+            format,
+        }) => {
+            cli::query(
+                query,
+                scholarly,
+                page as usize,
+                locale,
+                safe_search,
+                time_range,
+                format,
+            )
+            .await?
+        }
+        #[cfg(feature = "local-index")]
+        Some(cli::Command::Crawl) => cli::crawl().await?,
+    }
+
+    Ok(())
 }
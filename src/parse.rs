@@ -3,57 +3,69 @@
 use std::fmt::Debug;
 
 use crate::{
-    engines::{EngineFeaturedSnippet, EngineResponse, EngineSearchResult},
+    engines::{EngineFeaturedSnippet, EngineResponse, EngineSearchResult, FilterReason},
     normalize::normalize_url,
 };
 
 use scraper::{Html, Selector};
+use url::Url;
 
+/// The selectors used to find results in an engine's html. These are usually
+/// `'static` strings, but custom engines from the config borrow theirs.
 #[derive(Default, Debug)]
-pub struct ParseOpts {
-    result: &'static str,
-    title: QueryMethod,
-    href: QueryMethod,
-    description: QueryMethod,
-
-    featured_snippet: &'static str,
-    featured_snippet_title: QueryMethod,
-    featured_snippet_href: QueryMethod,
-    featured_snippet_description: QueryMethod,
+pub struct ParseOpts<'a> {
+    result: &'a str,
+    title: QueryMethod<'a>,
+    href: QueryMethod<'a>,
+    description: QueryMethod<'a>,
+
+    featured_snippet: &'a str,
+    featured_snippet_title: QueryMethod<'a>,
+    featured_snippet_href: QueryMethod<'a>,
+    featured_snippet_description: QueryMethod<'a>,
+
+    /// Relative hrefs are resolved against this, if it's set.
+    base_url: Option<&'a Url>,
 }
 
-impl ParseOpts {
+impl<'a> ParseOpts<'a> {
     #[must_use]
     pub fn new() -> Self {
         Self::default()
     }
 
     #[must_use]
-    pub fn result(mut self, result: &'static str) -> Self {
+    pub fn result(mut self, result: &'a str) -> Self {
         self.result = result;
         self
     }
 
     #[must_use]
-    pub fn title(mut self, title: impl Into<QueryMethod>) -> Self {
+    pub fn title(mut self, title: impl Into<QueryMethod<'a>>) -> Self {
         self.title = title.into();
         self
     }
 
     #[must_use]
-    pub fn href(mut self, href: impl Into<QueryMethod>) -> Self {
+    pub fn href(mut self, href: impl Into<QueryMethod<'a>>) -> Self {
         self.href = href.into();
         self
     }
 
     #[must_use]
-    pub fn description(mut self, description: impl Into<QueryMethod>) -> Self {
+    pub fn description(mut self, description: impl Into<QueryMethod<'a>>) -> Self {
         self.description = description.into();
         self
     }
 
     #[must_use]
-    pub fn featured_snippet(mut self, featured_snippet: &'static str) -> Self {
+    pub fn base_url(mut self, base_url: &'a Url) -> Self {
+        self.base_url = Some(base_url);
+        self
+    }
+
+    #[must_use]
+    pub fn featured_snippet(mut self, featured_snippet: &'a str) -> Self {
         self.featured_snippet = featured_snippet;
         self
     }
@@ -61,14 +73,17 @@ impl ParseOpts {
     #[must_use]
     pub fn featured_snippet_title(
         mut self,
-        featured_snippet_title: impl Into<QueryMethod>,
+        featured_snippet_title: impl Into<QueryMethod<'a>>,
     ) -> Self {
         self.featured_snippet_title = featured_snippet_title.into();
         self
     }
 
     #[must_use]
-    pub fn featured_snippet_href(mut self, featured_snippet_href: impl Into<QueryMethod>) -> Self {
+    pub fn featured_snippet_href(
+        mut self,
+        featured_snippet_href: impl Into<QueryMethod<'a>>,
+    ) -> Self {
         self.featured_snippet_href = featured_snippet_href.into();
         self
     }
@@ -76,7 +91,7 @@ impl ParseOpts {
     #[must_use]
     pub fn featured_snippet_description(
         mut self,
-        featured_snippet_description: impl Into<QueryMethod>,
+        featured_snippet_description: impl Into<QueryMethod<'a>>,
     ) -> Self {
         self.featured_snippet_description = featured_snippet_description.into();
         self
@@ -86,14 +101,14 @@ impl ParseOpts {
 type ManualQueryMethod = Box<dyn Fn(&scraper::ElementRef) -> eyre::Result<String>>;
 
 #[derive(Default)]
-pub enum QueryMethod {
+pub enum QueryMethod<'a> {
     #[default]
     None,
-    CssSelector(&'static str),
+    CssSelector(&'a str),
     Manual(ManualQueryMethod),
 }
 
-impl Debug for QueryMethod {
+impl Debug for QueryMethod<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::None => write!(f, "None"),
@@ -103,17 +118,17 @@ impl Debug for QueryMethod {
     }
 }
 
-impl From<&'static str> for QueryMethod {
-    fn from(s: &'static str) -> Self {
+impl<'a> From<&'a str> for QueryMethod<'a> {
+    fn from(s: &'a str) -> Self {
         QueryMethod::CssSelector(s)
     }
 }
 
-impl QueryMethod {
+impl QueryMethod<'_> {
     pub fn call_with_css_selector_override(
         &self,
         el: &scraper::ElementRef,
-        with_css_selector: impl Fn(&scraper::ElementRef, &'static str) -> Option<String>,
+        with_css_selector: impl Fn(&scraper::ElementRef, &str) -> Option<String>,
     ) -> eyre::Result<String> {
         match self {
             QueryMethod::None => Ok(String::new()),
@@ -129,6 +144,18 @@ impl QueryMethod {
                 .map(|n| n.text().collect::<String>())
         })
     }
+
+    /// Like [`QueryMethod::call`], but css selectors use the element's `href`
+    /// if it has one.
+    pub fn call_for_href(&self, el: &scraper::ElementRef) -> eyre::Result<String> {
+        self.call_with_css_selector_override(el, |el, s| {
+            el.select(&Selector::parse(s).unwrap()).next().map(|n| {
+                n.value()
+                    .attr("href")
+                    .map_or_else(|| n.text().collect::<String>(), str::to_string)
+            })
+        })
+    }
 }
 
 pub(super) fn parse_html_response_with_opts(
@@ -138,6 +165,7 @@ pub(super) fn parse_html_response_with_opts(
     let dom = Html::parse_document(body);
 
     let mut search_results = Vec::new();
+    let mut filtered_results = Vec::new();
 
     let ParseOpts {
         result: result_item_query,
@@ -148,21 +176,23 @@ pub(super) fn parse_html_response_with_opts(
         featured_snippet_title: featured_snippet_title_query_method,
         featured_snippet_href: featured_snippet_href_query_method,
         featured_snippet_description: featured_snippet_description_query_method,
+        base_url,
     } = opts;
 
+    let resolve_href = |href: String| match base_url {
+        Some(base_url) if !href.is_empty() => {
+            base_url.join(&href).map_or(href, |url| url.to_string())
+        }
+        _ => href,
+    };
+
     let result_item_query = Selector::parse(result_item_query).unwrap();
 
     let results = dom.select(&result_item_query);
 
     for result in results {
         let title = title_query_method.call(&result)?;
-        let url = href_query_method.call_with_css_selector_override(&result, |el, s| {
-            el.select(&Selector::parse(s).unwrap()).next().map(|n| {
-                n.value()
-                    .attr("href")
-                    .map_or_else(|| n.text().collect::<String>(), str::to_string)
-            })
-        })?;
+        let url = resolve_href(href_query_method.call_for_href(&result)?);
         let description = description_query_method.call(&result)?;
 
         // this can happen on google if you search "roll d6"
@@ -173,6 +203,14 @@ pub(super) fn parse_html_response_with_opts(
 
         // this can happen on google if it gives you a featured snippet
         if description.is_empty() {
+            filtered_results.push((
+                EngineSearchResult {
+                    url: normalize_url(&url).unwrap_or(url),
+                    title,
+                    description,
+                },
+                FilterReason::EmptyDescription,
+            ));
             continue;
         }
 
@@ -192,7 +230,8 @@ pub(super) fn parse_html_response_with_opts(
         .next()
     {
         let title = featured_snippet_title_query_method.call(&featured_snippet)?;
-        let url = featured_snippet_href_query_method.call(&featured_snippet)?;
+        let url =
+            resolve_href(featured_snippet_href_query_method.call_for_href(&featured_snippet)?);
         let url = normalize_url(&url)?;
         let description = featured_snippet_description_query_method.call(&featured_snippet)?;
 
@@ -217,5 +256,28 @@ pub(super) fn parse_html_response_with_opts(
         // these fields are used by instant answers, not normal search engines
         answer_html: None,
         infobox_html: None,
+        filtered_results,
     })
 }
+
+/// What engines can wrap the words that matched in, in descriptions. The
+/// results page shows these highlighted, everything else in a description is
+/// plain text.
+pub const HIGHLIGHT_START: &str = "<mark>";
+pub const HIGHLIGHT_END: &str = "</mark>";
+
+/// A description without its highlights, for places that only show plain text.
+#[must_use]
+pub fn strip_highlights(description: &str) -> String {
+    description
+        .replace(HIGHLIGHT_START, "")
+        .replace(HIGHLIGHT_END, "")
+}
+
+/// Get the text from some html, with whitespace collapsed. Used for showing
+/// answers and infoboxes somewhere that isn't html.
+pub fn html_to_text(html: &str) -> String {
+    let fragment = Html::parse_fragment(html);
+    let text = fragment.root_element().text().collect::<String>();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
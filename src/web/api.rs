@@ -0,0 +1,208 @@
+//! Search results as json, for tools that don't want to parse our html.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+};
+
+use axum::{
+    extract::{ConnectInfo, Query},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect},
+    Json,
+};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{
+    engines::{
+        self, Engine, EngineProgressUpdate, EngineResponse, Infobox, ProgressUpdateData, Response,
+        SafeSearch, TimeRange,
+    },
+    web::search::{remove_blocked_results, NotSearched, SearchRequest},
+};
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EngineState {
+    /// The engine never finished, this usually means it errored without
+    /// telling us why.
+    #[default]
+    Pending,
+    Done,
+    Skipped,
+    Error,
+    TimedOut,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct EngineStatus {
+    pub state: EngineState,
+    /// When the engine last changed state, in milliseconds since the search
+    /// started.
+    pub time_ms: u64,
+    /// Whether the response came from the cache.
+    pub cached: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The engine's own response before it was merged, only included in debug
+    /// mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<EngineResponse>,
+}
+
+/// The results of a search once every engine has finished.
+pub struct CompletedSearch {
+    pub response: Response,
+    pub postsearch_infobox: Option<Infobox>,
+    pub engines: BTreeMap<Engine, EngineStatus>,
+}
+
+/// Run a search and wait for all of it to finish, instead of streaming it like
+/// the html results page does.
+pub async fn search_to_completion(request: SearchRequest) -> eyre::Result<CompletedSearch> {
+    let SearchRequest {
+        query,
+        include_scholarly,
+        enabled_engines,
+        blocked_domains,
+        debug,
+        ..
+    } = request;
+
+    let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
+    let search_future = tokio::spawn(async move {
+        engines::search(
+            query,
+            include_scholarly,
+            enabled_engines,
+            debug,
+            progress_tx,
+        )
+        .await
+    });
+
+    let mut response = None;
+    let mut postsearch_infobox = None;
+    let mut engine_statuses = BTreeMap::<Engine, EngineStatus>::new();
+    while let Some(progress_update) = progress_rx.recv().await {
+        match progress_update.data {
+            ProgressUpdateData::Engine { engine, update } => {
+                let status = engine_statuses.entry(engine).or_default();
+                status.time_ms = progress_update.time_ms;
+                match update {
+                    EngineProgressUpdate::Done { cached } => {
+                        status.state = EngineState::Done;
+                        status.cached = cached;
+                    }
+                    EngineProgressUpdate::Skipping => status.state = EngineState::Skipped,
+                    EngineProgressUpdate::Error(reason) => {
+                        status.state = EngineState::Error;
+                        status.error = Some(reason);
+                    }
+                    EngineProgressUpdate::TimedOut => status.state = EngineState::TimedOut,
+                    EngineProgressUpdate::Requesting
+                    | EngineProgressUpdate::Downloading
+                    | EngineProgressUpdate::Parsing => {}
+                }
+            }
+            ProgressUpdateData::EngineResponse {
+                engine,
+                response: engine_response,
+            } => {
+                // instant answers don't send any progress updates, so this is
+                // the only way we know they finished
+                let status = engine_statuses.entry(engine).or_default();
+                if matches!(status.state, EngineState::Pending) {
+                    status.state = EngineState::Done;
+                    status.time_ms = progress_update.time_ms;
+                }
+                status.response = engine_response;
+            }
+            ProgressUpdateData::PartialResponse(_) => {}
+            ProgressUpdateData::Response(res) => response = Some(res),
+            ProgressUpdateData::PostSearchInfobox(infobox) => postsearch_infobox = Some(infobox),
+        }
+    }
+    search_future.await??;
+
+    let Some(mut response) = response else {
+        eyre::bail!("search finished without a response");
+    };
+    remove_blocked_results(&mut response, &blocked_domains, debug);
+
+    Ok(CompletedSearch {
+        response,
+        postsearch_infobox,
+        engines: engine_statuses,
+    })
+}
+
+#[derive(Serialize)]
+struct ApiResponse {
+    query: String,
+    page: usize,
+    locale: String,
+    safe_search: SafeSearch,
+    time_range: Option<TimeRange>,
+    #[serde(flatten)]
+    response: Response,
+    postsearch_infobox: Option<Infobox>,
+    engines: BTreeMap<Engine, EngineStatus>,
+}
+
+fn error_response(status: StatusCode, message: &str) -> axum::response::Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+pub async fn route(
+    Query(params): Query<HashMap<String, String>>,
+    cookies: axum_extra::extract::CookieJar,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+) -> axum::response::Response {
+    let format = params.get("format").map_or("json", String::as_str);
+    if format != "json" {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            &format!("unsupported format '{format}'"),
+        );
+    }
+
+    let request = match SearchRequest::new(&params, &cookies, &headers, addr) {
+        Ok(request) => request,
+        Err(NotSearched::Bang(url)) => return Redirect::to(&url).into_response(),
+        Err(NotSearched::Rejected) => {
+            return error_response(StatusCode::BAD_REQUEST, "query not allowed")
+        }
+        Err(NotSearched::EmptyQuery) => {
+            return error_response(StatusCode::BAD_REQUEST, "no query provided")
+        }
+    };
+
+    let query = request.query.query.clone();
+    let page = request.query.page;
+    let locale = request.query.locale.to_string();
+    let safe_search = request.query.safe_search;
+    let time_range = request.query.time_range;
+
+    let completed_search = match search_to_completion(request).await {
+        Ok(completed_search) => completed_search,
+        Err(err) => {
+            log::error!("Api search error for '{query}': {err}");
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, &err.to_string());
+        }
+    };
+
+    Json(ApiResponse {
+        query,
+        page,
+        locale,
+        safe_search,
+        time_range,
+        response: completed_search.response,
+        postsearch_infobox: completed_search.postsearch_infobox,
+        engines: completed_search.engines,
+    })
+    .into_response()
+}
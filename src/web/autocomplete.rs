@@ -7,10 +7,15 @@ use axum::{
     Json,
 };
 
-use crate::{engines, web::DISALLOWED_CHARACTERS};
+use crate::{
+    bangs,
+    engines::{self, SafeSearch, SearchQuery},
+    web::{get_custom_bangs, get_locale, get_safe_search, DISALLOWED_CHARACTERS},
+};
 
 pub async fn route(
     Query(params): Query<HashMap<String, String>>,
+    cookies: axum_extra::extract::CookieJar,
     headers: HeaderMap,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
 ) -> impl IntoResponse {
@@ -37,13 +42,41 @@ pub async fn route(
 
     log::info!("Autocomplete request from {ip} for '{query}'");
 
-    let query = if rustrict::CensorStr::is_inappropriate(query.as_str()) {
+    // suggest bangs while one is being typed
+    if let Some(prefix) = query
+        .rsplit(' ')
+        .next()
+        .and_then(|word| word.strip_prefix('!'))
+    {
+        let before_bang = &query[..query.len() - prefix.len() - 1];
+        let suggestions = bangs::suggest(prefix, &get_custom_bangs(&cookies))
+            .into_iter()
+            .take(10)
+            .map(|trigger| format!("{before_bang}!{trigger}"))
+            .collect::<Vec<_>>();
+        return (StatusCode::OK, Json((query, suggestions)));
+    }
+
+    let safe_search = get_safe_search(&cookies);
+    let query = if safe_search != SafeSearch::Off
+        && rustrict::CensorStr::is_inappropriate(query.as_str())
+    {
         rustrict::CensorStr::censor(query.as_str())
     } else {
         query
     };
 
-    let res = match engines::autocomplete(&query).await {
+    let search_query = SearchQuery {
+        query: query.clone(),
+        request_headers: Default::default(),
+        ip,
+        page: 1,
+        locale: get_locale(&params, &cookies, &headers),
+        safe_search,
+        time_range: None,
+    };
+
+    let res = match engines::autocomplete(&search_query).await {
         Ok(res) => res,
         Err(err) => {
             log::error!("Autocomplete error for '{query}': {}", err);
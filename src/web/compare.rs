@@ -0,0 +1,274 @@
+//! A page that shows what every engine returned for a query side by side,
+//! before anything is merged. Useful for deciding on engine weights.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    net::SocketAddr,
+};
+
+use axum::{
+    extract::{ConnectInfo, Query},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect},
+};
+use html_escape::{encode_double_quoted_attribute, encode_text};
+
+use crate::{
+    engines::{self, Engine, EngineProgressUpdate, EngineResponse, ProgressUpdateData},
+    web::search::{NotSearched, SearchRequest},
+};
+
+struct EngineColumn {
+    response: EngineResponse,
+    time_ms: u64,
+}
+
+fn render_overlap_table(columns: &BTreeMap<Engine, EngineColumn>) -> String {
+    // only engines with results are compared, answer engines would just be
+    // rows of zeroes
+    let urls = columns
+        .iter()
+        .filter(|(_, column)| !column.response.search_results.is_empty())
+        .map(|(engine, column)| {
+            let urls = column
+                .response
+                .search_results
+                .iter()
+                .map(|result| result.url.as_str())
+                .collect::<BTreeSet<_>>();
+            (*engine, urls)
+        })
+        .collect::<Vec<_>>();
+    if urls.len() < 2 {
+        return String::new();
+    }
+
+    let mut html = String::from("<tr><th></th>");
+    for (engine, _) in &urls {
+        html.push_str(&format!("<th>{}</th>", encode_text(engine.id())));
+    }
+    html.push_str("</tr>");
+
+    for (engine, engine_urls) in &urls {
+        html.push_str(&format!("<tr><th>{}</th>", encode_text(engine.id())));
+        for (other_engine, other_urls) in &urls {
+            if engine == other_engine || engine_urls.is_empty() {
+                html.push_str("<td>-</td>");
+                continue;
+            }
+            let shared = engine_urls.intersection(other_urls).count();
+            let percent = shared as f64 / engine_urls.len() as f64 * 100.;
+            html.push_str(&format!("<td>{percent:.0}%</td>"));
+        }
+        html.push_str("</tr>");
+    }
+
+    format!(
+        r#"<table class="compare-overlap">{html}</table>
+<p class="compare-overlap-note">the percentage of each row's results that the column's engine also returned</p>"#
+    )
+}
+
+fn render_engine_column(
+    engine: Engine,
+    column: &EngineColumn,
+    url_engines: &HashMap<&str, Vec<Engine>>,
+) -> String {
+    let response = &column.response;
+    let mut html = format!(
+        r#"<h2 class="compare-column-engine">{engine}</h2>
+<p class="compare-column-info">weight {weight}, {count} results in {time_ms}ms</p>"#,
+        engine = encode_text(engine.id()),
+        weight = engine.weight(),
+        count = response.search_results.len(),
+        time_ms = column.time_ms,
+    );
+
+    if let Some(featured_snippet) = &response.featured_snippet {
+        html.push_str(&format!(
+            r#"<div class="compare-featured-snippet">featured snippet: <a rel="noreferrer" href="{url_attr}">{title}</a></div>"#,
+            url_attr = encode_double_quoted_attribute(&featured_snippet.url),
+            title = encode_text(&featured_snippet.title),
+        ));
+    }
+    if response.answer_html.is_some() {
+        html.push_str(r#"<div class="compare-extra">returned an answer</div>"#);
+    }
+    if response.infobox_html.is_some() {
+        html.push_str(r#"<div class="compare-extra">returned an infobox</div>"#);
+    }
+
+    html.push_str("<ol>");
+    for result in &response.search_results {
+        let other_engines = url_engines
+            .get(result.url.as_str())
+            .into_iter()
+            .flatten()
+            .filter(|other_engine| **other_engine != engine)
+            .map(|other_engine| other_engine.id())
+            .collect::<Vec<_>>();
+        let (class, shared_html) = if other_engines.is_empty() {
+            ("compare-result", String::new())
+        } else {
+            (
+                "compare-result compare-result-shared",
+                format!(
+                    r#"<span class="compare-result-shared-engines">also in {}</span>"#,
+                    encode_text(&other_engines.join(", "))
+                ),
+            )
+        };
+        html.push_str(&format!(
+            r#"<li class="{class}"><a rel="noreferrer" href="{url_attr}">{title}</a><span class="compare-result-url">{url}</span>{shared_html}</li>"#,
+            url_attr = encode_double_quoted_attribute(&result.url),
+            title = encode_text(&result.title),
+            url = encode_text(&result.url),
+        ));
+    }
+    html.push_str("</ol>");
+
+    if !response.filtered_results.is_empty() {
+        html.push_str(&format!(
+            r#"<p class="compare-column-info">{} results left out while parsing</p>"#,
+            response.filtered_results.len()
+        ));
+    }
+
+    format!(r#"<div class="compare-column">{html}</div>"#)
+}
+
+fn render_compare_page(
+    query: &str,
+    columns: &BTreeMap<Engine, EngineColumn>,
+    engine_errors: &[(Engine, String)],
+) -> String {
+    // which engines returned each url, so shared results can be highlighted
+    let mut url_engines: HashMap<&str, Vec<Engine>> = HashMap::new();
+    for (engine, column) in columns {
+        for result in &column.response.search_results {
+            url_engines.entry(&result.url).or_default().push(*engine);
+        }
+    }
+
+    let mut errors_html = String::new();
+    for (engine, reason) in engine_errors {
+        errors_html.push_str(&format!(
+            r#"<p class="engine-error"><span class="engine-error-engine">{engine}</span> failed: {reason}</p>"#,
+            engine = encode_text(engine.id()),
+            reason = encode_text(reason),
+        ));
+    }
+
+    let columns_html = columns
+        .iter()
+        .map(|(engine, column)| render_engine_column(*engine, column, &url_engines))
+        .collect::<String>();
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>{title} - compare - metasearch</title>
+    <link rel="stylesheet" href="/style.css">
+</head>
+<body>
+    <div class="compare-container">
+    <a class="icon-wrapper" href="/"><img class="icon" style="height:1.5em;" src="/icons/house.svg"> <span>Home</span></a>
+    <a class="icon-wrapper" href="/settings"><img class="icon" style="height:1.5em;" src="/icons/gear.svg"> <span>Settings</span></a>
+    <hr />
+    <form action="/compare" method="get" class="search-form">
+        <input type="text" name="q" placeholder="Compare" value="{value}" autofocus onfocus="this.select()" autocomplete="off">
+        <input type="submit" value="Compare">
+    </form>
+    <div class="engine-errors">{errors_html}</div>
+    {overlap_html}
+    <div class="compare-columns">{columns_html}</div>
+    </div>
+</body>
+</html>
+"#,
+        title = encode_text(query),
+        value = encode_double_quoted_attribute(query),
+        overlap_html = render_overlap_table(columns),
+    )
+}
+
+pub async fn route(
+    Query(params): Query<HashMap<String, String>>,
+    cookies: axum_extra::extract::CookieJar,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+) -> axum::response::Response {
+    let request = match SearchRequest::new(&params, &cookies, &headers, addr) {
+        Ok(request) => request,
+        Err(NotSearched::Bang(url)) => return Redirect::to(&url).into_response(),
+        Err(NotSearched::Rejected | NotSearched::EmptyQuery) => {
+            return StatusCode::BAD_REQUEST.into_response()
+        }
+    };
+    let query = request.query.query.clone();
+
+    let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
+    let search_future = tokio::spawn(async move {
+        engines::search(
+            request.query,
+            request.include_scholarly,
+            request.enabled_engines,
+            true,
+            progress_tx,
+        )
+        .await
+    });
+
+    let mut columns = BTreeMap::new();
+    let mut engine_errors = Vec::new();
+    while let Some(progress_update) = progress_rx.recv().await {
+        match progress_update.data {
+            // engines with nothing to say for this query don't get a column
+            ProgressUpdateData::EngineResponse {
+                engine,
+                response: Some(response),
+            } if !response.is_empty() => {
+                columns.insert(
+                    engine,
+                    EngineColumn {
+                        response,
+                        time_ms: progress_update.time_ms,
+                    },
+                );
+            }
+            ProgressUpdateData::Engine {
+                engine,
+                update: EngineProgressUpdate::Error(reason),
+            } => engine_errors.push((engine, reason)),
+            ProgressUpdateData::Engine {
+                engine,
+                update: EngineProgressUpdate::TimedOut,
+            } => engine_errors.push((engine, "timed out".to_string())),
+            _ => {}
+        }
+    }
+
+    let search_result = match search_future.await {
+        Ok(search_result) => search_result,
+        Err(err) => Err(err.into()),
+    };
+    if let Err(err) = search_result {
+        log::error!("Compare error for '{query}': {err}");
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+            format!("<h1>Error: {}</h1>", encode_text(&err.to_string())),
+        )
+            .into_response();
+    }
+
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+        render_compare_page(&query, &columns, &engine_errors),
+    )
+        .into_response()
+}
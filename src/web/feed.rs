@@ -0,0 +1,193 @@
+//! Search results as RSS or Atom feeds with OpenSearch response elements, so
+//! queries can be subscribed to in feed readers and used by OpenSearch
+//! clients.
+
+use std::{collections::HashMap, net::SocketAddr};
+
+use axum::{
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect},
+};
+use html_escape::{encode_double_quoted_attribute, encode_text};
+
+use crate::{
+    engines::SearchResult,
+    parse::strip_highlights,
+    web::{
+        api::search_to_completion,
+        search::{results_page_url, NotSearched, SearchRequest},
+    },
+};
+
+const OPENSEARCH_NAMESPACE: &str = "http://a9.com/-/spec/opensearch/1.1/";
+/// Merged pages don't have a fixed size, but OpenSearch clients need one to
+/// work out where each page starts, so feeds only have this many of the top
+/// results.
+const ITEMS_PER_PAGE: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedFormat {
+    Rss,
+    Atom,
+}
+
+/// The parts of the feed that are the same for RSS and Atom.
+struct FeedInfo<'a> {
+    query: &'a str,
+    page: usize,
+    /// Where the feed's query can be seen in the browser.
+    html_url: String,
+    opensearch_url: String,
+    results: Vec<SearchResult>,
+}
+
+impl FeedInfo<'_> {
+    fn render_opensearch_elements(&self) -> String {
+        let start_index = (self.page - 1) * ITEMS_PER_PAGE + 1;
+        format!(
+            r#"<opensearch:totalResults>{total}</opensearch:totalResults>
+<opensearch:startIndex>{start_index}</opensearch:startIndex>
+<opensearch:itemsPerPage>{ITEMS_PER_PAGE}</opensearch:itemsPerPage>
+<opensearch:Query role="request" searchTerms="{search_terms}" startPage="{page}" />
+"#,
+            // we don't know how many results there are past this page, so
+            // this only counts the ones up to the end of it
+            total = start_index - 1 + self.results.len(),
+            search_terms = encode_double_quoted_attribute(self.query),
+            page = self.page,
+        )
+    }
+}
+
+fn render_rss(feed: &FeedInfo) -> String {
+    let mut items = String::new();
+    for result in &feed.results {
+        items.push_str(&format!(
+            r#"<item>
+<title>{title}</title>
+<link>{url}</link>
+<guid isPermaLink="true">{url}</guid>
+<description>{description}</description>
+</item>
+"#,
+            title = encode_text(&result.title),
+            url = encode_text(&result.url),
+            description = encode_text(&strip_highlights(&result.description)),
+        ));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:opensearch="{OPENSEARCH_NAMESPACE}" xmlns:atom="http://www.w3.org/2005/Atom">
+<channel>
+<title>{title} - metasearch</title>
+<link>{html_url}</link>
+<description>Search results for {title}</description>
+<lastBuildDate>{date}</lastBuildDate>
+<atom:link rel="search" type="application/opensearchdescription+xml" href="{opensearch_url}" />
+{opensearch_elements}{items}</channel>
+</rss>
+"#,
+        title = encode_text(feed.query),
+        html_url = encode_text(&feed.html_url),
+        date = chrono::Utc::now().to_rfc2822(),
+        opensearch_url = encode_double_quoted_attribute(&feed.opensearch_url),
+        opensearch_elements = feed.render_opensearch_elements(),
+    )
+}
+
+fn render_atom(feed: &FeedInfo) -> String {
+    let updated = chrono::Utc::now().to_rfc3339();
+
+    let mut entries = String::new();
+    for result in &feed.results {
+        entries.push_str(&format!(
+            r#"<entry>
+<title>{title}</title>
+<link href="{url_attr}" />
+<id>{url}</id>
+<updated>{updated}</updated>
+<summary>{description}</summary>
+</entry>
+"#,
+            title = encode_text(&result.title),
+            url_attr = encode_double_quoted_attribute(&result.url),
+            url = encode_text(&result.url),
+            description = encode_text(&strip_highlights(&result.description)),
+        ));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xmlns:opensearch="{OPENSEARCH_NAMESPACE}">
+<title>{title} - metasearch</title>
+<link href="{html_url_attr}" />
+<link rel="search" type="application/opensearchdescription+xml" href="{opensearch_url}" />
+<id>{html_url}</id>
+<updated>{updated}</updated>
+<author><name>metasearch</name></author>
+{opensearch_elements}{entries}</feed>
+"#,
+        title = encode_text(feed.query),
+        html_url_attr = encode_double_quoted_attribute(&feed.html_url),
+        html_url = encode_text(&feed.html_url),
+        opensearch_url = encode_double_quoted_attribute(&feed.opensearch_url),
+        opensearch_elements = feed.render_opensearch_elements(),
+    )
+}
+
+pub async fn route(
+    format: FeedFormat,
+    params: HashMap<String, String>,
+    cookies: axum_extra::extract::CookieJar,
+    headers: HeaderMap,
+    addr: SocketAddr,
+) -> axum::response::Response {
+    let request = match SearchRequest::new(&params, &cookies, &headers, addr) {
+        Ok(request) => request,
+        Err(NotSearched::Bang(url)) => return Redirect::to(&url).into_response(),
+        Err(NotSearched::Rejected | NotSearched::EmptyQuery) => {
+            return StatusCode::BAD_REQUEST.into_response()
+        }
+    };
+
+    let host = headers
+        .get("host")
+        .and_then(|host| host.to_str().ok())
+        .unwrap_or("localhost");
+    let query = request.query.query.clone();
+    let page = request.query.page;
+    let html_url = format!(
+        "https://{host}{}",
+        results_page_url(
+            &request.query,
+            request.include_scholarly,
+            &request.sticky_params,
+            page
+        )
+    );
+
+    let completed_search = match search_to_completion(request).await {
+        Ok(completed_search) => completed_search,
+        Err(err) => {
+            log::error!("Feed search error for '{query}': {err}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let mut results = completed_search.response.search_results;
+    results.truncate(ITEMS_PER_PAGE);
+    let feed = FeedInfo {
+        query: &query,
+        page,
+        html_url,
+        opensearch_url: format!("https://{host}/opensearch.xml"),
+        results,
+    };
+
+    let (content_type, body) = match format {
+        FeedFormat::Rss => ("application/rss+xml; charset=utf-8", render_rss(&feed)),
+        FeedFormat::Atom => ("application/atom+xml; charset=utf-8", render_atom(&feed)),
+    };
+    ([(header::CONTENT_TYPE, content_type)], body).into_response()
+}
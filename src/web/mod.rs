@@ -1,14 +1,30 @@
+pub mod api;
 pub mod autocomplete;
+pub mod compare;
+pub mod feed;
 pub mod opensearch;
 pub mod search;
+pub mod searxng;
 pub mod settings;
 
-use std::{collections::BTreeMap, net::SocketAddr};
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+};
 
-use axum::{http::header, routing::get, Router};
+use axum::{
+    http::{header, HeaderMap},
+    routing::get,
+    Router,
+};
 
-use crate::engines::Engine;
+use crate::{
+    config,
+    engines::{Engine, SafeSearch},
+    locale::Locale,
+};
 
+/// The default address to listen on, can be overridden in the config.
 pub const BIND_ADDRESS: &str = "0.0.0.0:28019";
 
 pub const DISALLOWED_CHARACTERS: &[char] = &[
@@ -93,14 +109,22 @@ pub async fn run() {
         .route("/block_site", get(settings::block_route))
         .route("/unblock_site", get(settings::unblock_route))
         .route("/set_search_engines", get(settings::search_engines_route))
+        .route("/set_locale", get(settings::locale_route))
+        .route("/set_safe_search", get(settings::safe_search_route))
+        .route("/add_bang", get(settings::add_bang_route))
+        .route("/remove_bang", get(settings::remove_bang_route))
         .route("/rand_noscript", get(crate::engines::answer::random::route))
         .route("/opensearch.xml", get(opensearch::route))
         .route("/search", get(search::route))
+        .route("/compare", get(compare::route))
+        .route("/api/search", get(api::route))
         .route("/autocomplete", get(autocomplete::route));
 
-    log::info!("Listening on {BIND_ADDRESS}");
+    let bind_address = &config::get().bind;
 
-    let listener = tokio::net::TcpListener::bind(BIND_ADDRESS).await.unwrap();
+    log::info!("Listening on {bind_address}");
+
+    let listener = tokio::net::TcpListener::bind(bind_address).await.unwrap();
     axum::serve(
         listener,
         app.into_make_service_with_connect_info::<SocketAddr>(),
@@ -148,7 +172,7 @@ pub fn get_enabled_search_engines(
 pub fn set_enabled_search_engines(enabled_search_engines: &BTreeMap<String, bool>) -> String {
     let mut first_iter = true;
     let mut built_string = String::new();
-    for (engine, enabled) in enabled_search_engines.into_iter() {
+    for (engine, enabled) in enabled_search_engines.iter() {
         if first_iter {
             first_iter = false;
         } else {
@@ -205,3 +229,84 @@ where
     let blocked_domains_base64 = BASE64_STANDARD.encode(built_string);
     format!("blocked={blocked_domains_base64}")
 }
+
+/// Get the locale a search should be made in. A `locale` query param takes
+/// priority, then the locale chosen in the settings, then whatever the
+/// browser says it wants.
+pub fn get_locale(
+    params: &HashMap<String, String>,
+    cookies: &axum_extra::extract::cookie::CookieJar,
+    headers: &HeaderMap,
+) -> Locale {
+    params
+        .get("locale")
+        .and_then(|locale| Locale::parse(locale))
+        .or_else(|| get_locale_setting(cookies))
+        .or_else(|| {
+            headers
+                .get(header::ACCEPT_LANGUAGE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(Locale::from_accept_language)
+        })
+        .unwrap_or_default()
+}
+
+/// The locale the user picked in their settings, if any.
+pub fn get_locale_setting(cookies: &axum_extra::extract::cookie::CookieJar) -> Option<Locale> {
+    cookies
+        .get("locale")
+        .and_then(|cookie| Locale::parse(cookie.value()))
+}
+
+/// Passing `None` removes the cookie, so the browser's locale gets used again.
+pub fn set_locale_setting(locale: Option<&Locale>) -> String {
+    match locale {
+        Some(locale) => format!("locale={locale}"),
+        None => "locale=; Max-Age=0".to_string(),
+    }
+}
+
+/// The safe search level from the settings, or the default from the config.
+pub fn get_safe_search(cookies: &axum_extra::extract::cookie::CookieJar) -> SafeSearch {
+    cookies
+        .get("safe_search")
+        .and_then(|cookie| cookie.value().parse().ok())
+        .unwrap_or(config::get().search.safe_search)
+}
+
+pub fn set_safe_search(safe_search: SafeSearch) -> String {
+    format!("safe_search={safe_search}")
+}
+
+/// The user's own bangs, mapping each bang (without the `!`) to its url.
+pub fn get_custom_bangs(
+    cookies: &axum_extra::extract::cookie::CookieJar,
+) -> BTreeMap<String, String> {
+    use base64::prelude::*;
+
+    cookies
+        .get("bangs")
+        .map(|cookie| {
+            let bangs_base64 = BASE64_STANDARD.decode(cookie.value()).unwrap_or_default();
+            let bangs_str = std::str::from_utf8(&bangs_base64).unwrap_or_default();
+            // urls can have commas in them so bangs are separated by newlines
+            bangs_str
+                .lines()
+                .filter_map(|line| line.split_once('='))
+                .map(|(trigger, url)| (trigger.trim().to_lowercase(), url.trim().to_string()))
+                .filter(|(trigger, url)| !trigger.is_empty() && !url.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn set_custom_bangs(bangs: &BTreeMap<String, String>) -> String {
+    let built_string = bangs
+        .iter()
+        .map(|(trigger, url)| format!("{trigger}={url}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    use base64::prelude::*;
+    let bangs_base64 = BASE64_STANDARD.encode(built_string);
+    format!("bangs={bangs_base64}")
+}
@@ -21,6 +21,10 @@ pub async fn route(headers: HeaderMap) -> impl IntoResponse {
         <Description>Search metasearch</Description>
         <InputEncoding>UTF-8</InputEncoding>
         <Url type="text/html" method="get" template="https://{host}/search?q={{searchTerms}}" />
+        <Url type="application/rss+xml" method="get"
+            template="https://{host}/search?q={{searchTerms}}&amp;format=rss&amp;page={{startPage?}}" />
+        <Url type="application/atom+xml" method="get"
+            template="https://{host}/search?q={{searchTerms}}&amp;format=atom&amp;page={{startPage?}}" />
         <Url type="application/x-suggestions+json" method="get"
             template="https://{host}/autocomplete?q={{searchTerms}}" />
     </OpenSearchDescription>"#
@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     net::SocketAddr,
 };
 
@@ -11,16 +11,62 @@ use axum::{
     response::{IntoResponse, Redirect},
 };
 use bytes::Bytes;
-use html_escape::{encode_text, encode_unquoted_attribute};
+use html_escape::{encode_double_quoted_attribute, encode_text, encode_unquoted_attribute};
 
 use crate::{
-    engines::{self, Engine, EngineProgressUpdate, ProgressUpdateData, Response, SearchQuery},
-    web::{get_enabled_search_engines, DISALLOWED_CHARACTERS},
+    bangs,
+    engines::{
+        self, Engine, EngineProgressUpdate, FilterReason, FilteredResult, ProgressUpdateData,
+        Response, SafeSearch, SearchQuery, TimeRange, MAX_PAGE,
+    },
+    locale::Locale,
+    parse::{HIGHLIGHT_END, HIGHLIGHT_START},
+    web::{
+        api,
+        feed::{self, FeedFormat},
+        get_enabled_search_engines, searxng, DISALLOWED_CHARACTERS,
+    },
 };
 
-use super::get_blocked_domains;
+use super::{get_blocked_domains, get_custom_bangs, get_locale, get_safe_search};
+
+/// Search options from the query string that should stick around for the next
+/// search and when changing pages, like a `locale` override.
+type StickyParams = Vec<(&'static str, String)>;
+
+fn render_time_range_select(time_range: Option<TimeRange>) -> String {
+    let mut html = String::from(r#"<select name="time_range" class="time-range-select">"#);
+    html.push_str(r#"<option value="">any time</option>"#);
+    for range in TimeRange::ALL {
+        let is_selected = if Some(*range) == time_range {
+            " selected"
+        } else {
+            ""
+        };
+        html.push_str(&format!(
+            r#"<option value="{range}"{is_selected}>past {range}</option>"#
+        ));
+    }
+    html.push_str("</select>");
+    html
+}
+
+fn render_beginning_of_html(
+    query: &SearchQuery,
+    include_scholarly: bool,
+    sticky_params: &StickyParams,
+) -> String {
+    let time_range_select = render_time_range_select(query.time_range);
+    let hidden_inputs = sticky_params
+        .iter()
+        .map(|(name, value)| {
+            format!(
+                r#"<input type="hidden" name="{name}" value="{}">"#,
+                encode_double_quoted_attribute(value)
+            )
+        })
+        .collect::<String>();
 
-fn render_beginning_of_html(query: &str, include_scholarly: bool) -> String {
     format!(
         r#"<!DOCTYPE html>
 <html lang="en">
@@ -44,16 +90,68 @@ fn render_beginning_of_html(query: &str, include_scholarly: bool) -> String {
             <img src="/icons/graduation_cap.svg" class="scholarly-checkbox-image">
         </label>
         <input type="text" name="q" placeholder="Search" value="{}" id="search-input" autofocus onfocus="this.select()" autocomplete="off">
+        {time_range_select}
+        {hidden_inputs}
         <input type="submit" value="Search">
     </form>
     <div class="progress-updates">
 "#,
-        encode_text(query),
-        encode_unquoted_attribute(query),
+        encode_text(&query.query),
+        encode_unquoted_attribute(&query.query),
         scholarly_toggle = if include_scholarly { "checked" } else { "" }
     )
 }
 
+/// The path of a page of results for the search, with the same settings.
+pub(super) fn results_page_url(
+    query: &SearchQuery,
+    include_scholarly: bool,
+    sticky_params: &StickyParams,
+    page: usize,
+) -> String {
+    let mut url = format!("/search?q={}", urlencoding::encode(&query.query));
+    if include_scholarly {
+        url.push_str("&scholarly=on");
+    }
+    if let Some(time_range) = query.time_range {
+        url.push_str(&format!("&time_range={time_range}"));
+    }
+    for (name, value) in sticky_params {
+        url.push_str(&format!("&{name}={}", urlencoding::encode(value)));
+    }
+    if page > 1 {
+        url.push_str(&format!("&page={page}"));
+    }
+    url
+}
+
+fn render_pagination(
+    query: &SearchQuery,
+    include_scholarly: bool,
+    sticky_params: &StickyParams,
+) -> String {
+    let page = query.page;
+    let page_url = |page: usize| results_page_url(query, include_scholarly, sticky_params, page);
+
+    let mut html = String::new();
+    if page > 1 {
+        html.push_str(&format!(
+            r#"<a class="pagination-previous" href="{url}">&larr; previous</a>"#,
+            url = encode_double_quoted_attribute(&page_url(page - 1))
+        ));
+    }
+    html.push_str(&format!(
+        r#"<span class="pagination-page">page {page}</span>"#
+    ));
+    if page < MAX_PAGE {
+        html.push_str(&format!(
+            r#"<a class="pagination-next" href="{url}">next &rarr;</a>"#,
+            url = encode_double_quoted_attribute(&page_url(page.saturating_add(1)))
+        ));
+    }
+    format!(r#"<nav class="pagination">{html}</nav>"#)
+}
+
 fn render_end_of_html() -> String {
     "</main></div></body></html>".to_string()
 }
@@ -74,7 +172,65 @@ fn render_engine_list(engines: &[engines::Engine]) -> String {
     format!(r#"<div class="engine-list">{html}</div>"#)
 }
 
-fn render_search_result(result: &engines::SearchResult) -> String {
+/// Show how a result's score was calculated, for debug mode.
+fn render_search_result_debug(result: &engines::SearchResult) -> String {
+    let mut rows = String::new();
+    for ranking in &result.rankings {
+        rows.push_str(&format!(
+            "<tr><td>{engine}</td><td>{position}</td><td>{weight}</td><td>{score:.4}</td></tr>",
+            engine = encode_text(ranking.engine.id()),
+            position = ranking.position,
+            weight = ranking.weight,
+            score = ranking.score,
+        ));
+    }
+    format!(
+        r#"<div class="search-result-debug">
+    <table>
+        <tr><th>engine</th><th>position</th><th>weight</th><th>score</th></tr>
+        {rows}
+        <tr class="search-result-debug-total"><td colspan="3">total</td><td>{score:.4}</td></tr>
+    </table>
+    <p>title and description from {title_engine}</p>
+    </div>"#,
+        score = result.score,
+        title_engine = encode_text(result.title_engine.id()),
+    )
+}
+
+/// A description as html. Only the highlights engines marked are kept as
+/// tags, everything else is escaped.
+fn render_description(description: &str) -> String {
+    let mut html = String::new();
+    let mut rest = description;
+    let mut highlighted = false;
+    loop {
+        let tag = if highlighted {
+            HIGHLIGHT_END
+        } else {
+            HIGHLIGHT_START
+        };
+        let Some((before, after)) = rest.split_once(tag) else {
+            html.push_str(&encode_text(rest));
+            break;
+        };
+        html.push_str(&encode_text(before));
+        html.push_str(tag);
+        highlighted = !highlighted;
+        rest = after;
+    }
+    if highlighted {
+        html.push_str(HIGHLIGHT_END);
+    }
+    html
+}
+
+fn render_search_result(result: &engines::SearchResult, debug: bool) -> String {
+    let debug_html = if debug {
+        render_search_result_debug(result)
+    } else {
+        String::new()
+    };
     format!(
         r#"<div class="search-result">
     <a class="search-result-anchor" rel="noreferrer" href="{url_attr}">
@@ -83,12 +239,13 @@ fn render_search_result(result: &engines::SearchResult) -> String {
     </a>
     <p class="search-result-description">{desc}</p>
     {engines_html}
+    {debug_html}
     </div>
 "#,
         url_attr = encode_unquoted_attribute(&result.url),
         url = encode_text(&result.url),
         title = encode_text(&result.title),
-        desc = encode_text(&result.description),
+        desc = render_description(&result.description),
         engines_html = render_engine_list(&result.engines.iter().copied().collect::<Vec<_>>())
     )
 }
@@ -112,8 +269,75 @@ fn render_featured_snippet(featured_snippet: &engines::FeaturedSnippet) -> Strin
     )
 }
 
-fn render_results(response: Response, blocked_domains: &HashSet<String>) -> String {
+fn is_blocked(url: &str, blocked_domains: &HashSet<String>) -> bool {
+    let Ok(url) = url.parse::<url::Url>() else {
+        return false;
+    };
+    let Some(domain) = url.domain() else {
+        return false;
+    };
+    blocked_domains.contains(domain)
+        || domain
+            .strip_prefix("www.")
+            .is_some_and(|domain| blocked_domains.contains(domain))
+}
+
+/// Remove results from domains the user blocked. In debug mode they're moved
+/// into the filtered results instead, once for each engine that found them.
+pub fn remove_blocked_results(
+    response: &mut Response,
+    blocked_domains: &HashSet<String>,
+    debug: bool,
+) {
+    let (blocked, allowed) = std::mem::take(&mut response.search_results)
+        .into_iter()
+        .partition::<Vec<_>, _>(|result| is_blocked(&result.url, blocked_domains));
+    response.search_results = allowed;
+    if !debug {
+        return;
+    }
+    for result in blocked {
+        response
+            .filtered_results
+            .extend(result.engines.iter().map(|&engine| FilteredResult {
+                url: result.url.clone(),
+                title: result.title.clone(),
+                engine,
+                reason: FilterReason::BlockedDomain,
+            }));
+    }
+}
+
+/// List every result that was left out and why, for debug mode.
+fn render_filtered_results(filtered_results: &[FilteredResult]) -> String {
+    if filtered_results.is_empty() {
+        return String::new();
+    }
+
+    let mut html = String::new();
+    for result in filtered_results {
+        html.push_str(&format!(
+            r#"<li><span class="filtered-result-engine">{engine}</span> <a rel="noreferrer" href="{url_attr}">{title}</a> <span class="filtered-result-url">{url}</span>: {reason}</li>"#,
+            engine = encode_text(result.engine.id()),
+            url_attr = encode_double_quoted_attribute(&result.url),
+            title = encode_text(&result.title),
+            url = encode_text(&result.url),
+            reason = result.reason,
+        ));
+    }
+    format!(
+        r#"<details class="filtered-results"><summary>{count} filtered results</summary><ul>{html}</ul></details>"#,
+        count = filtered_results.len()
+    )
+}
+
+fn render_results(
+    mut response: Response,
+    blocked_domains: &HashSet<String>,
+    debug: bool,
+) -> String {
     let mut html = String::new();
+    remove_blocked_results(&mut response, blocked_domains, debug);
 
     if let Some(infobox) = &response.infobox {
         html.push_str(&format!(
@@ -135,19 +359,7 @@ fn render_results(response: Response, blocked_domains: &HashSet<String>) -> Stri
     }
 
     for result in &response.search_results {
-        if let Ok(url) = result.url.parse::<url::Url>() {
-            if let Some(domain) = url.domain() {
-                if blocked_domains.contains(domain) {
-                    continue;
-                }
-                if let Some(domain) = domain.strip_prefix("www.") {
-                    if blocked_domains.contains(domain) {
-                        continue;
-                    }
-                }
-            }
-        }
-        html.push_str(&render_search_result(result));
+        html.push_str(&render_search_result(result, debug));
     }
 
     if response.infobox.is_none()
@@ -158,6 +370,69 @@ fn render_results(response: Response, blocked_domains: &HashSet<String>) -> Stri
         html.push_str(r#"<p>No results.</p>"#);
     }
 
+    if debug {
+        html.push_str(&render_filtered_results(&response.filtered_results));
+    }
+
+    html
+}
+
+fn render_engine_errors(engine_errors: &[(Engine, String)]) -> String {
+    if engine_errors.is_empty() {
+        return String::new();
+    }
+
+    let mut html = String::new();
+    for (engine, reason) in engine_errors {
+        html.push_str(&format!(
+            r#"<p class="engine-error"><span class="engine-error-engine">{engine}</span> failed: {reason}</p>"#,
+            engine = encode_text(engine.id()),
+            reason = encode_text(reason),
+        ));
+    }
+    format!(r#"<div class="engine-errors">{html}</div>"#)
+}
+
+/// Render a set of results, which might be partial if we're still waiting on
+/// some engines. Every render gets its own container that replaces the
+/// previous one, with an inline script if javascript is enabled and with css
+/// if it isn't, so results can be streamed in by only appending to the page.
+fn render_results_update(
+    render_index: usize,
+    results: Response,
+    pending_engines: Option<&BTreeSet<Engine>>,
+    engine_errors: &[(Engine, String)],
+    blocked_domains: &HashSet<String>,
+    debug: bool,
+) -> String {
+    let mut html = String::new();
+
+    if render_index == 0 {
+        html.push_str("</div>"); // close progress-updates
+        html.push_str("<style>.progress-updates{display:none}</style>");
+    }
+
+    html.push_str(&format!(
+        r#"<div class="search-results" id="search-results-{render_index}">"#
+    ));
+    if let Some(pending_engines) = pending_engines {
+        let pending_engines = pending_engines.iter().copied().collect::<Vec<_>>();
+        html.push_str(&format!(
+            r#"<div class="search-results-pending">waiting for {engines_html}</div>"#,
+            engines_html = render_engine_list(&pending_engines)
+        ));
+    }
+    html.push_str(&render_engine_errors(engine_errors));
+    html.push_str(&render_results(results, blocked_domains, debug));
+    html.push_str("</div>");
+
+    if render_index > 0 {
+        let previous_id = format!("search-results-{}", render_index - 1);
+        html.push_str(&format!(
+            r#"<style>#{previous_id}{{display:none}}</style><script>document.getElementById("{previous_id}").remove()</script>"#
+        ));
+    }
+
     html
 }
 
@@ -167,80 +442,219 @@ fn render_engine_progress_update(
     time_ms: u64,
 ) -> String {
     let message = match progress_update {
-        EngineProgressUpdate::Requesting => "requesting",
-        EngineProgressUpdate::Downloading => "downloading",
-        EngineProgressUpdate::Parsing => "parsing",
-        EngineProgressUpdate::Done => "<span class=\"progress-update-done\">done</span>",
+        EngineProgressUpdate::Requesting => "requesting".to_string(),
+        EngineProgressUpdate::Downloading => "downloading".to_string(),
+        EngineProgressUpdate::Parsing => "parsing".to_string(),
+        EngineProgressUpdate::Done { cached: false } => {
+            "<span class=\"progress-update-done\">done</span>".to_string()
+        }
+        EngineProgressUpdate::Done { cached: true } => {
+            "<span class=\"progress-update-done\">done</span> <span class=\"progress-update-cached\">(cached)</span>".to_string()
+        }
         EngineProgressUpdate::Skipping => {
-            "<span class=\"progress-update-skipping\">skipping</span>"
+            "<span class=\"progress-update-skipping\">skipping</span>".to_string()
         }
+        EngineProgressUpdate::TimedOut => {
+            "<span class=\"progress-update-error\">timed out</span>".to_string()
+        }
+        EngineProgressUpdate::Error(reason) => format!(
+            "<span class=\"progress-update-error\">error: {}</span>",
+            encode_text(reason)
+        ),
     };
 
     format!(r#"<span class="progress-update-time">{time_ms:>4}ms</span> {engine} {message}"#)
 }
 
-pub async fn route(
-    Query(params): Query<HashMap<String, String>>,
-    cookies: axum_extra::extract::CookieJar,
-    headers: HeaderMap,
-    ConnectInfo(addr): ConnectInfo<SocketAddr>,
-) -> Result<impl IntoResponse, impl IntoResponse> {
-    let blocked_domains = get_blocked_domains::<HashSet<_>>(&cookies);
+/// Everything from a search request that's needed to search, shared by the
+/// html results page and the api.
+pub struct SearchRequest {
+    pub query: SearchQuery,
+    pub include_scholarly: bool,
+    pub enabled_engines: BTreeMap<String, bool>,
+    pub blocked_domains: HashSet<String>,
+    /// Shows how every result was ranked and which results were filtered out.
+    pub debug: bool,
+    pub sticky_params: StickyParams,
+}
 
-    let include_scholarly = params.get("scholarly").map(|v| v.to_lowercase()) == Some("on".into());
-    let enabled_engines = get_enabled_search_engines(&cookies);
+/// Why a search request didn't turn into a search.
+pub enum NotSearched {
+    /// The query had a bang, so we should go to this url instead.
+    Bang(String),
+    /// The query had control characters or was inappropriate.
+    Rejected,
+    EmptyQuery,
+}
 
-    let ip = headers
-        .get(std::env::var("IP_HEADER").unwrap_or("x-forwarded-for".into()))
-        .map_or_else(
-            || addr.ip().to_string(),
-            |ip| ip.to_str().unwrap_or_default().to_string(),
-        );
+impl SearchRequest {
+    pub fn new(
+        params: &HashMap<String, String>,
+        cookies: &axum_extra::extract::CookieJar,
+        headers: &HeaderMap,
+        addr: SocketAddr,
+    ) -> Result<Self, NotSearched> {
+        let blocked_domains = get_blocked_domains::<HashSet<_>>(cookies);
+
+        let include_scholarly =
+            params.get("scholarly").map(|v| v.to_lowercase()) == Some("on".into());
+        let page = params
+            .get("page")
+            .and_then(|page| page.parse::<usize>().ok())
+            .filter(|page| *page >= 1)
+            .map_or(1, |page| page.min(MAX_PAGE));
+        let enabled_engines = get_enabled_search_engines(cookies);
+        let locale = get_locale(params, cookies, headers);
+        let safe_search = get_safe_search(cookies);
+        // the search form sends an empty time range for "any time"
+        let time_range = params
+            .get("time_range")
+            .and_then(|time_range| time_range.parse::<TimeRange>().ok());
+        let debug = params.get("debug").is_some_and(|debug| debug == "1");
+
+        let mut sticky_params = StickyParams::new();
+        if let Some(locale) = params
+            .get("locale")
+            .and_then(|locale| Locale::parse(locale))
+        {
+            sticky_params.push(("locale", locale.to_string()));
+        }
+        if debug {
+            sticky_params.push(("debug", "1".to_string()));
+        }
 
-    let query = params
-        .get("q")
-        .cloned()
-        .unwrap_or_default()
-        .trim()
-        .replace('\n', " ");
+        let ip = headers
+            .get(std::env::var("IP_HEADER").unwrap_or("x-forwarded-for".into()))
+            .map_or_else(
+                || addr.ip().to_string(),
+                |ip| ip.to_str().unwrap_or_default().to_string(),
+            );
 
-    if DISALLOWED_CHARACTERS
-        .iter()
-        .any(|char| query.contains(*char))
-    {
-        return Err(Redirect::to("https://youtu.be/dQw4w9WgXcQ"));
+        let query = params
+            .get("q")
+            .cloned()
+            .unwrap_or_default()
+            .trim()
+            .replace('\n', " ");
+
+        if DISALLOWED_CHARACTERS
+            .iter()
+            .any(|char| query.contains(*char))
+        {
+            return Err(NotSearched::Rejected);
+        }
+
+        log::info!("Search request from {ip} for '{query}'");
+
+        // bangs go straight to another site, so we don't have to search at all
+        if let Some(url) = bangs::redirect_url(&query, &get_custom_bangs(cookies)) {
+            return Err(NotSearched::Bang(url));
+        }
+
+        // inappropriate queries are only allowed if safe search is off
+        if safe_search != SafeSearch::Off && rustrict::CensorStr::is_inappropriate(query.as_str()) {
+            return Err(NotSearched::Rejected);
+        }
+        if query.is_empty() {
+            return Err(NotSearched::EmptyQuery);
+        }
+
+        let query = SearchQuery {
+            query,
+            request_headers: headers
+                .clone()
+                .into_iter()
+                .map(|(k, v)| {
+                    (
+                        k.map(|k| k.to_string()).unwrap_or_default(),
+                        v.to_str().unwrap_or_default().to_string(),
+                    )
+                })
+                .collect(),
+            ip,
+            page,
+            locale,
+            safe_search,
+            time_range,
+        };
+
+        Ok(Self {
+            query,
+            include_scholarly,
+            enabled_engines,
+            blocked_domains,
+            debug,
+            sticky_params,
+        })
     }
+}
 
-    log::info!("Search request from {ip} for '{query}'");
+/// Whether the client would rather have json than html, for content
+/// negotiation on `/search`.
+fn prefers_json(headers: &HeaderMap) -> bool {
+    let accept = headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .unwrap_or_default();
+    accept.contains("application/json") && !accept.contains("text/html")
+}
 
-    if rustrict::CensorStr::is_inappropriate(query.as_str()) {
-        return Err(Redirect::to("https://youtu.be/dQw4w9WgXcQ"));
+pub async fn route(
+    Query(params): Query<HashMap<String, String>>,
+    cookies: axum_extra::extract::CookieJar,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+) -> axum::response::Response {
+    match params.get("format").map(String::as_str) {
+        // SearXNG uses the same path for its json api, so tools made for it
+        // work with us too
+        Some("json") => {
+            return searxng::route(Query(params), cookies, headers, ConnectInfo(addr)).await;
+        }
+        Some("rss") => {
+            return feed::route(FeedFormat::Rss, params, cookies, headers, addr).await;
+        }
+        Some("atom") => {
+            return feed::route(FeedFormat::Atom, params, cookies, headers, addr).await;
+        }
+        None | Some("html") => {}
+        Some(format) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("Unsupported format '{format}'"),
+            )
+                .into_response();
+        }
     }
-    if query.is_empty() {
-        // redirect to index
-        return Ok((
-            StatusCode::FOUND,
-            [
-                (header::LOCATION, "/"),
-                (header::CONTENT_TYPE, "text/html; charset=utf-8"),
-            ],
-            Body::from("<a href=\"/\">No query provided, click here to go back to index</a>"),
-        ));
+    if prefers_json(&headers) {
+        return api::route(Query(params), cookies, headers, ConnectInfo(addr)).await;
     }
 
-    let query = SearchQuery {
+    let SearchRequest {
         query,
-        request_headers: headers
-            .clone()
-            .into_iter()
-            .map(|(k, v)| {
-                (
-                    k.map(|k| k.to_string()).unwrap_or_default(),
-                    v.to_str().unwrap_or_default().to_string(),
-                )
-            })
-            .collect(),
-        ip,
+        include_scholarly,
+        enabled_engines,
+        blocked_domains,
+        debug,
+        sticky_params,
+    } = match SearchRequest::new(&params, &cookies, &headers, addr) {
+        Ok(request) => request,
+        Err(NotSearched::Bang(url)) => return Redirect::to(&url).into_response(),
+        Err(NotSearched::Rejected) => {
+            return Redirect::to("https://youtu.be/dQw4w9WgXcQ").into_response()
+        }
+        Err(NotSearched::EmptyQuery) => {
+            // redirect to index
+            return (
+                StatusCode::FOUND,
+                [
+                    (header::LOCATION, "/"),
+                    (header::CONTENT_TYPE, "text/html; charset=utf-8"),
+                ],
+                Body::from("<a href=\"/\">No query provided, click here to go back to index</a>"),
+            )
+                .into_response();
+        }
     };
 
     let s = stream! {
@@ -249,34 +663,77 @@ pub async fn route(
         // the html is sent in three chunks (technically more if you count progress updates):
         // 1) the beginning of the html, including the search bar
         // 1.5) the progress updates
-        // 2) the results
+        // 2) the results, which might be rendered several times as engines respond
         // 3) the post-search infobox (usually not sent) + the end of the html
 
-        let first_part = render_beginning_of_html(&query, include_scholarly);
+        let first_part = render_beginning_of_html(&query, include_scholarly, &sticky_params);
+        let pagination_html = render_pagination(&query, include_scholarly, &sticky_params);
         // second part is in the loop
         let mut third_part = String::new();
+        // engines that failed, shown above the results
+        let mut engine_errors = Vec::new();
+        // engines we're still waiting on, shown above partial results
+        let mut pending_engines = BTreeSet::new();
+        let mut results_render_count = 0;
 
         yield R::Ok(Bytes::from(first_part));
 
         let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
 
-        let search_future = tokio::spawn(async move { engines::search(query, include_scholarly, enabled_engines, progress_tx).await });
+        let search_future = tokio::spawn(async move { engines::search(query, include_scholarly, enabled_engines, debug, progress_tx).await });
 
         while let Some(progress_update) = progress_rx.recv().await {
             match progress_update.data {
                 ProgressUpdateData::Engine { engine, update } => {
-                    let progress_html = format!(
-                        r#"<p class="progress-update">{}</p>"#,
-                        render_engine_progress_update(engine, &update, progress_update.time_ms)
+                    match &update {
+                        EngineProgressUpdate::Requesting => {
+                            pending_engines.insert(engine);
+                        }
+                        EngineProgressUpdate::Done { .. } => {
+                            pending_engines.remove(&engine);
+                        }
+                        EngineProgressUpdate::Error(reason) => {
+                            pending_engines.remove(&engine);
+                            engine_errors.push((engine, reason.clone()));
+                        }
+                        EngineProgressUpdate::TimedOut => {
+                            pending_engines.remove(&engine);
+                            engine_errors.push((engine, "timed out".to_string()));
+                        }
+                        _ => {}
+                    }
+                    // the progress updates are hidden once we start showing results
+                    if results_render_count == 0 {
+                        let progress_html = format!(
+                            r#"<p class="progress-update">{}</p>"#,
+                            render_engine_progress_update(engine, &update, progress_update.time_ms)
+                        );
+                        yield R::Ok(Bytes::from(progress_html));
+                    }
+                },
+                ProgressUpdateData::PartialResponse(results) => {
+                    let second_part = render_results_update(
+                        results_render_count,
+                        results,
+                        Some(&pending_engines),
+                        &engine_errors,
+                        &blocked_domains,
+                        debug,
                     );
-                    yield R::Ok(Bytes::from(progress_html));
+                    results_render_count += 1;
+                    yield Ok(Bytes::from(second_part));
                 },
+                ProgressUpdateData::EngineResponse { .. } => {},
                 ProgressUpdateData::Response(results) => {
-                    let mut second_part = String::new();
-
-                    second_part.push_str("</div>"); // close progress-updates
-                    second_part.push_str("<style>.progress-updates{display:none}</style>");
-                    second_part.push_str(&render_results(results, &blocked_domains));
+                    let second_part = render_results_update(
+                        results_render_count,
+                        results,
+                        None,
+                        &engine_errors,
+                        &blocked_domains,
+                        debug,
+                    );
+                    results_render_count += 1;
                     yield Ok(Bytes::from(second_part));
                 },
                 ProgressUpdateData::PostSearchInfobox(infobox) => {
@@ -298,6 +755,7 @@ pub async fn route(
             return;
         };
 
+        third_part.push_str(&pagination_html);
         third_part.push_str(&render_end_of_html());
 
         yield Ok(Bytes::from(third_part));
@@ -306,12 +764,46 @@ pub async fn route(
 
     let stream = Body::from_stream(s);
 
-    Ok((
+    (
         StatusCode::OK,
         [
             (header::CONTENT_TYPE, "text/html; charset=utf-8"),
             (header::TRANSFER_ENCODING, "chunked"),
         ],
         stream,
-    ))
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn description_is_escaped() {
+        assert_eq!(
+            render_description("a <b>bold</b> & claim"),
+            "a &lt;b&gt;bold&lt;/b&gt; &amp; claim"
+        );
+    }
+
+    #[test]
+    fn description_keeps_highlights() {
+        assert_eq!(
+            render_description("the <mark>rust</mark> <book>"),
+            "the <mark>rust</mark> &lt;book&gt;"
+        );
+    }
+
+    #[test]
+    fn description_highlights_are_balanced() {
+        assert_eq!(
+            render_description("</mark>a <mark>b"),
+            "&lt;/mark&gt;a <mark>b</mark>"
+        );
+        assert_eq!(
+            render_description("<mark>a <mark>b</mark>"),
+            "<mark>a &lt;mark&gt;b</mark>"
+        );
+    }
 }
@@ -0,0 +1,259 @@
+//! The same json as SearXNG's `/search?format=json`, so tools made for SearXNG
+//! can use metasearch without any changes.
+
+use std::{collections::HashMap, net::SocketAddr};
+
+use axum::{
+    extract::{ConnectInfo, Query},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect},
+    Json,
+};
+use serde::Serialize;
+use url::Url;
+
+use crate::{
+    engines::{self, Engine, FeaturedSnippet, Infobox, SearchQuery, SearchResult},
+    parse::{html_to_text, strip_highlights},
+    web::{
+        api::{search_to_completion, EngineState},
+        search::{NotSearched, SearchRequest},
+    },
+};
+
+#[derive(Serialize)]
+struct SearxngResponse {
+    query: String,
+    number_of_results: usize,
+    results: Vec<SearxngResult>,
+    answers: Vec<String>,
+    corrections: Vec<String>,
+    infoboxes: Vec<SearxngInfobox>,
+    suggestions: Vec<String>,
+    unresponsive_engines: Vec<(Engine, String)>,
+}
+
+#[derive(Serialize)]
+struct SearxngResult {
+    url: String,
+    title: String,
+    content: String,
+    /// The engine the title and content came from.
+    engine: Engine,
+    engines: Vec<Engine>,
+    /// Where each engine ranked the result, starting at 1.
+    positions: Vec<usize>,
+    score: f64,
+    category: &'static str,
+    /// The url split up like Python's `urlparse`, which is what SearXNG uses.
+    parsed_url: [String; 6],
+    template: &'static str,
+}
+
+#[derive(Serialize)]
+struct SearxngInfobox {
+    infobox: String,
+    id: Option<String>,
+    content: String,
+    urls: Vec<SearxngInfoboxUrl>,
+    engine: Engine,
+    engines: Vec<Engine>,
+}
+
+#[derive(Serialize)]
+struct SearxngInfoboxUrl {
+    title: String,
+    url: String,
+}
+
+impl From<SearchResult> for SearxngResult {
+    fn from(result: SearchResult) -> Self {
+        let parsed_url = Url::parse(&result.url).map_or_else(
+            |_| Default::default(),
+            |url| {
+                [
+                    url.scheme().to_string(),
+                    url.host_str().unwrap_or_default().to_string(),
+                    url.path().to_string(),
+                    String::new(),
+                    url.query().unwrap_or_default().to_string(),
+                    url.fragment().unwrap_or_default().to_string(),
+                ]
+            },
+        );
+        Self {
+            url: result.url,
+            title: result.title,
+            content: strip_highlights(&result.description),
+            engine: result.title_engine,
+            engines: result.engines.into_iter().collect(),
+            positions: result
+                .rankings
+                .iter()
+                .map(|ranking| ranking.position)
+                .collect(),
+            score: result.score,
+            category: "general",
+            parsed_url,
+            template: "default.html",
+        }
+    }
+}
+
+impl From<FeaturedSnippet> for SearxngInfobox {
+    fn from(featured_snippet: FeaturedSnippet) -> Self {
+        Self {
+            infobox: featured_snippet.title.clone(),
+            id: Some(featured_snippet.url.clone()),
+            content: featured_snippet.description,
+            urls: vec![SearxngInfoboxUrl {
+                title: featured_snippet.title,
+                url: featured_snippet.url,
+            }],
+            engine: featured_snippet.engine,
+            engines: vec![featured_snippet.engine],
+        }
+    }
+}
+
+impl From<Infobox> for SearxngInfobox {
+    fn from(infobox: Infobox) -> Self {
+        // our infoboxes are html without a separate title, so the engine's
+        // name is used instead
+        Self {
+            infobox: infobox.engine.id().to_string(),
+            id: None,
+            content: html_to_text(&infobox.html),
+            urls: Vec::new(),
+            engine: infobox.engine,
+            engines: vec![infobox.engine],
+        }
+    }
+}
+
+/// Turn SearXNG's query parameters into ours. Ours are used if both are given.
+fn translate_params(mut params: HashMap<String, String>) -> HashMap<String, String> {
+    if let Some(page) = params.get("pageno").cloned() {
+        params.entry("page".to_string()).or_insert(page);
+    }
+    if let Some(language) = params.get("language").cloned() {
+        // "all" and "auto" mean the same as not setting a locale
+        if !matches!(language.as_str(), "all" | "auto") {
+            params.entry("locale".to_string()).or_insert(language);
+        }
+    }
+    if params
+        .get("categories")
+        .is_some_and(|categories| categories.split(',').any(|c| c.trim() == "science"))
+    {
+        params.insert("scholarly".to_string(), "on".to_string());
+    }
+    // SearXNG's engines parameter works like our @engine selectors. engines we
+    // don't have are left out, since they'd otherwise be searched for
+    if let Some(engines) = params.get("engines").cloned() {
+        let selectors = engines
+            .split(',')
+            .map(str::trim)
+            .filter(|engine| Engine::from_id(engine).is_some())
+            .map(|engine| format!("@{engine} "))
+            .collect::<String>();
+        if let Some(query) = params.get_mut("q") {
+            query.insert_str(0, &selectors);
+        }
+    }
+    params
+}
+
+pub async fn route(
+    Query(params): Query<HashMap<String, String>>,
+    cookies: axum_extra::extract::CookieJar,
+    headers: HeaderMap,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+) -> axum::response::Response {
+    // the query without the selectors translate_params adds, which is what the
+    // client searched for
+    let query = params
+        .get("q")
+        .map(|query| query.trim().replace('\n', " "))
+        .unwrap_or_default();
+    let params = translate_params(params);
+
+    let request = match SearchRequest::new(&params, &cookies, &headers, addr) {
+        Ok(request) => request,
+        Err(NotSearched::Bang(url)) => return Redirect::to(&url).into_response(),
+        Err(NotSearched::Rejected | NotSearched::EmptyQuery) => {
+            return StatusCode::BAD_REQUEST.into_response()
+        }
+    };
+
+    // SearXNG's suggestions are related searches, autocomplete is the closest
+    // thing we have to that
+    let autocomplete_query = SearchQuery {
+        query: query.clone(),
+        request_headers: Default::default(),
+        ip: request.query.ip.clone(),
+        page: 1,
+        locale: request.query.locale.clone(),
+        safe_search: request.query.safe_search,
+        time_range: None,
+    };
+
+    let (completed_search, suggestions) = tokio::join!(
+        search_to_completion(request),
+        engines::autocomplete(&autocomplete_query)
+    );
+    let completed_search = match completed_search {
+        Ok(completed_search) => completed_search,
+        Err(err) => {
+            log::error!("SearXNG api search error for '{query}': {err}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let suggestions = suggestions
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|suggestion| !suggestion.eq_ignore_ascii_case(&query))
+        .collect();
+
+    let response = completed_search.response;
+
+    let answers = response
+        .answer
+        .iter()
+        .map(|answer| html_to_text(&answer.html))
+        .collect();
+
+    let mut infoboxes = Vec::new();
+    if let Some(featured_snippet) = response.featured_snippet {
+        infoboxes.push(featured_snippet.into());
+    }
+    if let Some(infobox) = response.infobox.or(completed_search.postsearch_infobox) {
+        infoboxes.push(infobox.into());
+    }
+
+    let unresponsive_engines = completed_search
+        .engines
+        .into_iter()
+        .filter_map(|(engine, status)| match status.state {
+            EngineState::Error => Some((engine, status.error.unwrap_or_default())),
+            EngineState::TimedOut => Some((engine, "timeout".to_string())),
+            _ => None,
+        })
+        .collect();
+
+    Json(SearxngResponse {
+        query,
+        number_of_results: response.search_results.len(),
+        results: response
+            .search_results
+            .into_iter()
+            .map(SearxngResult::from)
+            .collect(),
+        answers,
+        corrections: Vec::new(),
+        infoboxes,
+        suggestions,
+        unresponsive_engines,
+    })
+    .into_response()
+}
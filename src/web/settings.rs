@@ -3,31 +3,43 @@ use std::collections::{BTreeMap, BTreeSet, HashMap};
 use axum::{
     body::Body,
     extract::Query,
-    http::{header, StatusCode},
+    http::{header, HeaderMap, StatusCode},
     response::{IntoResponse, Redirect},
 };
-use html_escape::encode_single_quoted_attribute;
+use html_escape::{encode_single_quoted_attribute, encode_text};
 
-use crate::engines::Engine;
+use crate::{
+    bangs,
+    engines::{Engine, SafeSearch},
+    locale::Locale,
+};
 
 use super::{
-    get_blocked_domains, get_enabled_search_engines, set_blocked_domains,
-    set_enabled_search_engines,
+    get_blocked_domains, get_custom_bangs, get_enabled_search_engines, get_locale,
+    get_locale_setting, get_safe_search, set_blocked_domains, set_custom_bangs,
+    set_enabled_search_engines, set_locale_setting, set_safe_search,
 };
 
-pub async fn route(cookies: axum_extra::extract::cookie::CookieJar) -> impl IntoResponse {
+pub async fn route(
+    cookies: axum_extra::extract::cookie::CookieJar,
+    headers: HeaderMap,
+) -> impl IntoResponse {
     let enabled_search_engines = get_enabled_search_engines(&cookies);
     let search_engines = enabled_search_engines
         .iter()
         .map(|(engine, enabled)| {
-            let engine_proper = match Engine::from_id(&engine) {
-                Some(engine) => engine.id_proper(),
+            let engine_name = match Engine::from_id(engine) {
+                Some(engine) => engine.name(),
                 None => "",
             };
             let is_checked = if *enabled { "checked" } else { "" };
+            let engine = encode_single_quoted_attribute(engine);
             let checkbox =
                 format!("<input type='checkbox' id='{engine}' name='{engine}' {is_checked} />");
-            let name_label = format!("<label for='{engine}'>{engine_proper}</label>");
+            let name_label = format!(
+                "<label for='{engine}'>{}</label>",
+                encode_text(engine_name)
+            );
             format!("<tr><td class='engine-checkbox'>{checkbox}</td><td class='engine-label'>{name_label}</td></tr>")
         })
         .collect::<Vec<_>>();
@@ -49,13 +61,60 @@ pub async fn route(cookies: axum_extra::extract::cookie::CookieJar) -> impl Into
         })
         .collect::<Vec<_>>();
 
+    let custom_bangs = get_custom_bangs(&cookies);
+    let sanitized_custom_bangs = custom_bangs
+        .iter()
+        .map(|(trigger, url)| {
+            let form_header = "<form action='/remove_bang' method='get' enctype='application/x-www-form-urlencoded' class='remove-bang-form'>";
+            let text_display = format!(
+                "<input type='text' value='!{}' contenteditable='false' class='bang-trigger' /><input type='text' value='{}' contenteditable='false' class='bang-url' />",
+                encode_single_quoted_attribute(trigger),
+                encode_single_quoted_attribute(url)
+            );
+            let trigger_elem = format!(
+                "<input type='text' name='bang' value='{}' style='display:none;'>",
+                encode_single_quoted_attribute(trigger)
+            );
+            let return_elem = "<input type='text' name='return' value='/settings' style='display:none;'>";
+            let button = "<input type='submit' value='x' />";
+            let form_footer = "</form>";
+            format!("{form_header}{text_display}{trigger_elem}{return_elem}{button}{form_footer}")
+        })
+        .collect::<Vec<_>>();
+
+    let locale_setting = get_locale_setting(&cookies)
+        .map(|locale| locale.to_string())
+        .unwrap_or_default();
+    // what the locale would be without the setting, shown as the placeholder
+    let browser_locale = get_locale(&HashMap::new(), &Default::default(), &headers);
+
+    let safe_search = get_safe_search(&cookies);
+    let safe_search_options = SafeSearch::ALL
+        .iter()
+        .map(|level| {
+            let is_selected = if *level == safe_search {
+                "selected"
+            } else {
+                ""
+            };
+            format!("<option value='{level}' {is_selected}>{level}</option>")
+        })
+        .collect::<Vec<_>>();
+
     (
         StatusCode::OK,
         [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
         Body::from(
             include_str!("assets/settings.html")
                 .replace("%search-engines%", &search_engines.join(""))
-                .replace("%blocked-sites%", &sanitized_blocked_domains.join("")),
+                .replace("%blocked-sites%", &sanitized_blocked_domains.join(""))
+                .replace("%safe-search-options%", &safe_search_options.join(""))
+                .replace("%custom-bangs%", &sanitized_custom_bangs.join(""))
+                .replace("%locale%", &encode_single_quoted_attribute(&locale_setting))
+                .replace(
+                    "%browser-locale%",
+                    &encode_single_quoted_attribute(&browser_locale.to_string()),
+                ),
         ),
     )
 }
@@ -140,3 +199,130 @@ pub async fn search_engines_route(
         Redirect::to(return_url),
     ))
 }
+
+pub async fn locale_route(
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let Some(return_url) = params.get("return") else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            [(header::CONTENT_TYPE, "text/plain")],
+            Body::from(format!("missing `return` param\nparams:\n{params:?}")),
+        ));
+    };
+    // an empty locale means we should go back to using the browser's
+    let locale = params
+        .get("locale")
+        .map(|locale| locale.trim())
+        .unwrap_or_default();
+    let locale = if locale.is_empty() {
+        None
+    } else {
+        let Some(locale) = Locale::parse(locale) else {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                [(header::CONTENT_TYPE, "text/plain")],
+                Body::from(format!(
+                    "invalid locale `{locale}`, expected something like `en-US`"
+                )),
+            ));
+        };
+        Some(locale)
+    };
+    Ok((
+        [(header::SET_COOKIE, set_locale_setting(locale.as_ref()))],
+        Redirect::to(return_url),
+    ))
+}
+
+pub async fn safe_search_route(
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let Some(return_url) = params.get("return") else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            [(header::CONTENT_TYPE, "text/plain")],
+            Body::from(format!("missing `return` param\nparams:\n{params:?}")),
+        ));
+    };
+    let Some(safe_search) = params
+        .get("safe_search")
+        .and_then(|level| level.parse::<SafeSearch>().ok())
+    else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            [(header::CONTENT_TYPE, "text/plain")],
+            Body::from(format!(
+                "missing or invalid `safe_search` param\nparams:\n{params:?}"
+            )),
+        ));
+    };
+    Ok((
+        [(header::SET_COOKIE, set_safe_search(safe_search))],
+        Redirect::to(return_url),
+    ))
+}
+
+pub async fn add_bang_route(
+    Query(params): Query<HashMap<String, String>>,
+    cookies: axum_extra::extract::cookie::CookieJar,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let (Some(trigger), Some(url)) = (params.get("bang"), params.get("url")) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            [(header::CONTENT_TYPE, "text/plain")],
+            Body::from(format!(
+                "missing `bang` or `url` param\nparams:\n{params:?}"
+            )),
+        ));
+    };
+    let Some(return_url) = params.get("return") else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            [(header::CONTENT_TYPE, "text/plain")],
+            Body::from(format!("missing `return` param\nparams:\n{params:?}")),
+        ));
+    };
+    // people will probably type the ! too
+    let trigger = trigger.trim().trim_start_matches('!').to_lowercase();
+    let url = url.trim();
+    if let Err(err) = bangs::validate(&trigger, url) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            [(header::CONTENT_TYPE, "text/plain")],
+            Body::from(err.to_string()),
+        ));
+    }
+    let mut custom_bangs = get_custom_bangs(&cookies);
+    custom_bangs.insert(trigger, url.to_string());
+    Ok((
+        [(header::SET_COOKIE, set_custom_bangs(&custom_bangs))],
+        Redirect::to(return_url),
+    ))
+}
+
+pub async fn remove_bang_route(
+    Query(params): Query<HashMap<String, String>>,
+    cookies: axum_extra::extract::cookie::CookieJar,
+) -> Result<impl IntoResponse, impl IntoResponse> {
+    let Some(trigger) = params.get("bang") else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            [(header::CONTENT_TYPE, "text/plain")],
+            Body::from(format!("missing `bang` param\nparams:\n{params:?}")),
+        ));
+    };
+    let Some(return_url) = params.get("return") else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            [(header::CONTENT_TYPE, "text/plain")],
+            Body::from(format!("missing `return` param\nparams:\n{params:?}")),
+        ));
+    };
+    let mut custom_bangs = get_custom_bangs(&cookies);
+    custom_bangs.remove(trigger);
+    Ok((
+        [(header::SET_COOKIE, set_custom_bangs(&custom_bangs))],
+        Redirect::to(return_url),
+    ))
+}